@@ -1,4 +1,4 @@
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use clap_complete::Shell;
 
 #[derive(Parser, Debug)]
@@ -14,6 +14,27 @@ pub struct Cli {
     #[arg(short, long, value_name = "FILE", global = true)]
     pub file: Option<String>,
 
+    /// Number of tasks to run in parallel when executing dependencies
+    #[arg(
+        short = 'j',
+        long = "jobs",
+        value_name = "N",
+        global = true,
+        default_value_t = 1,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub jobs: usize,
+
+    /// Maximum number of nested trigger() calls before the run is aborted
+    #[arg(
+        long = "max-trigger-depth",
+        value_name = "N",
+        global = true,
+        default_value_t = crate::engine::DEFAULT_MAX_TRIGGER_DEPTH,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub max_trigger_depth: usize,
+
     #[command(subcommand)]
     pub cmd: Option<Commands>,
 }
@@ -24,6 +45,12 @@ pub enum Commands {
     List(ListOptions),
     /// Run a task (`rhask run -h` for details)
     Run(RunOptions),
+    /// Show a task's parameters, settings and where it is declared
+    Show(ShowOptions),
+    /// Show which tasks are up to date, stale, or have never run
+    Status(StatusOptions),
+    /// Manage the fingerprint cache in `.rhask/cache`
+    Cache(CacheCommand),
     /// Generate shell completion scripts
     Completions(CompletionCommand),
     /// Internal helper for shell completions
@@ -43,18 +70,70 @@ pub struct ListOptions {
     /// Print tasks as flat full paths (good for piping into fzf)
     #[arg(short = 'F', long = "flat")]
     pub flat: bool,
+
+    /// Show each task's effective dir() and env(), including group defaults, and where
+    /// every task and group is declared
+    #[arg(short = 's', long = "settings")]
+    pub settings: bool,
+
+    /// Print the task tree as json, yaml or tsv with full details instead of text
+    #[arg(long = "format", value_enum, default_value_t = ListFormat::Text)]
+    pub format: ListFormat,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ListFormat {
+    #[default]
+    Text,
+    Json,
+    Yaml,
+    Tsv,
+}
+
+#[derive(Args, Debug)]
+pub struct StatusOptions {
+    /// Group name to display (omit to show every task)
+    #[arg(name = "GROUP")]
+    pub group: Option<String>,
 }
 
 #[derive(Args, Debug)]
-#[command(trailing_var_arg = true)]
 pub struct RunOptions {
     /// Task name to execute
     #[arg(name = "TASK_NAME")]
     pub task: String,
 
+    /// Run tasks even when their outputs() are newer than their sources(). Put it before
+    /// TASK_NAME: after the task's first argument it is passed to the task instead
+    #[arg(long = "force")]
+    pub force: bool,
+
     /// Arguments passed to the task
     #[arg(name = "ARGS", allow_hyphen_values = true)]
     pub args: Vec<String>,
+
+    /// Arguments after `--`, handed to the task verbatim through `passthrough()`
+    #[arg(name = "PASSTHROUGH", last = true)]
+    pub passthrough: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct ShowOptions {
+    /// Task name (short or fully-qualified)
+    #[arg(name = "TASK_NAME")]
+    pub task: String,
+}
+
+#[derive(Args, Debug)]
+pub struct CacheCommand {
+    #[command(subcommand)]
+    pub action: CacheAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheAction {
+    /// Remove every recorded fingerprint so all tasks run again
+    Clean,
 }
 
 #[derive(Args, Debug)]
@@ -69,6 +148,10 @@ pub struct CompleteTasksCommand {
     /// Optional prefix used to filter task/group names
     #[arg(name = "PREFIX")]
     pub prefix: Option<String>,
+
+    /// List the options of this task (`--name<TAB>help`) instead of task names
+    #[arg(long = "args", value_name = "TASK")]
+    pub args_of: Option<String>,
 }
 
 pub fn parse_args() -> Cli {
@@ -102,6 +185,7 @@ mod tests {
             Commands::List(opts) => {
                 assert_eq!(opts.group.as_deref(), Some("ops.release"));
                 assert!(opts.flat);
+                assert!(!opts.settings);
             }
             other => panic!("expected list command, got {:?}", other),
         }
@@ -138,6 +222,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_run_keeps_arguments_after_double_dash() {
+        let cli = parse_from(["rhask", "run", "test", "--", "--nocapture", "-q"]);
+        match cli.cmd.expect("run command") {
+            Commands::Run(opts) => {
+                assert_eq!(opts.task, "test");
+                assert!(opts.args.is_empty());
+                assert_eq!(
+                    opts.passthrough,
+                    vec!["--nocapture".to_string(), "-q".to_string()]
+                );
+            }
+            other => panic!("expected run command, got {:?}", other),
+        }
+    }
+
     #[test]
     fn parse_direct_subcommand_with_arguments() {
         let cli = parse_from(["rhask", "-f", "tasks.rhai", "deploy", "--env=prod", "extra"]);
@@ -157,6 +257,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_jobs_option() {
+        let cli = parse_from(["rhask", "-j", "4", "ci"]);
+        assert_eq!(cli.jobs, 4);
+        assert!(Cli::try_parse_from(["rhask", "--jobs", "0", "ci"]).is_err());
+    }
+
+    #[test]
+    fn parse_run_with_force_flag() {
+        let cli = parse_from(["rhask", "run", "--force", "codegen", "release"]);
+        match cli.cmd.expect("run command") {
+            Commands::Run(opts) => {
+                assert!(opts.force);
+                assert_eq!(opts.task, "codegen");
+                assert_eq!(opts.args, vec!["release".to_string()]);
+            }
+            other => panic!("expected run command, got {:?}", other),
+        }
+
+        // Once the task's own arguments start, --force belongs to them.
+        let cli = parse_from(["rhask", "run", "codegen", "release", "--force"]);
+        match cli.cmd.expect("run command") {
+            Commands::Run(opts) => {
+                assert!(!opts.force);
+                assert_eq!(
+                    opts.args,
+                    vec!["release".to_string(), "--force".to_string()]
+                );
+            }
+            other => panic!("expected run command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_cache_clean_command() {
+        let cli = parse_from(["rhask", "cache", "clean"]);
+        match cli.cmd.expect("cache command") {
+            Commands::Cache(opts) => assert!(matches!(opts.action, CacheAction::Clean)),
+            other => panic!("expected cache command, got {:?}", other),
+        }
+    }
+
     #[test]
     fn parse_completions_command() {
         let cli = parse_from(["rhask", "completions", "bash"]);
@@ -45,6 +45,27 @@ __rhask_dynamic_tasks() {
     "${cmd[@]}" 2>/dev/null
 }
 
+__rhask_task_args() {
+    local task="$1"
+    local file=""
+    local idx=1
+    while [[ $idx -lt ${#COMP_WORDS[@]} ]]; do
+        case "${COMP_WORDS[$idx]}" in
+            --file|-f)
+                ((idx++))
+                file="${COMP_WORDS[$idx]}"
+                ;;
+        esac
+        ((idx++))
+    done
+    local cmd=(rhask)
+    if [[ -n "$file" ]]; then
+        cmd+=(--file "$file")
+    fi
+    cmd+=(complete-tasks --args "$task")
+    "${cmd[@]}" 2>/dev/null | cut -f1
+}
+
 _rhask() {
     local cur="${COMP_WORDS[COMP_CWORD]}"
     local first_cmd_idx=1
@@ -83,7 +104,7 @@ _rhask() {
 
     if [[ ${COMP_CWORD} -eq $first_cmd_idx ]]; then
         case "$cur" in
-            -*|list|run|completions|"")
+            -*|list|run|show|status|cache|completions|"")
                 ;;
             *)
                 local dynamic_candidates="$(__rhask_dynamic_tasks "$cur")"
@@ -93,7 +114,7 @@ _rhask() {
                 fi
                 ;;
         esac
-    elif [[ ${COMP_WORDS[1]} == run && ${COMP_CWORD} -eq 2 ]]; then
+    elif [[ ( ${COMP_WORDS[1]} == run || ${COMP_WORDS[1]} == show ) && ${COMP_CWORD} -eq 2 ]]; then
         case "$cur" in
             -*|"")
                 ;;
@@ -106,6 +127,27 @@ _rhask() {
                 ;;
         esac
     fi
+
+    local task_idx=$first_cmd_idx
+    if [[ ${COMP_WORDS[$first_cmd_idx]} == run ]]; then
+        task_idx=$((first_cmd_idx + 1))
+        while [[ $task_idx -lt ${COMP_CWORD} && ${COMP_WORDS[$task_idx]} == -* ]]; do
+            ((task_idx++))
+        done
+    fi
+    if [[ "$cur" == -* && ${COMP_CWORD} -gt $task_idx ]]; then
+        case "${COMP_WORDS[$first_cmd_idx]}" in
+            list|show|status|cache|completions|complete-tasks)
+                ;;
+            *)
+                local arg_candidates="$(__rhask_task_args "${COMP_WORDS[$task_idx]}")"
+                if [[ -n "$arg_candidates" ]]; then
+                    COMPREPLY=( $(compgen -W "$arg_candidates" -- "$cur") )
+                    return 0
+                fi
+                ;;
+        esac
+    fi
     __rhask_base "$@"
 }
 "#;
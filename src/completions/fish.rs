@@ -44,7 +44,7 @@ function __fish_rhask_should_complete_tasks_direct
     or return 1
     string match -q -- '-*' "$current_token"
     and return 1
-    for reserved in list run completions
+    for reserved in list run show status cache completions
         test "$current_token" = $reserved
         and return 1
     end
@@ -52,7 +52,7 @@ function __fish_rhask_should_complete_tasks_direct
 end
 
 function __fish_rhask_should_complete_tasks_run
-    __fish_rhask_using_subcommand run
+    __fish_rhask_using_subcommand run show
     or return 1
     set -l tokens (commandline -opc)
     set -l total (count $tokens)
@@ -62,7 +62,7 @@ function __fish_rhask_should_complete_tasks_run
     while test $idx -le $total
         set token $tokens[$idx]
         if test $run_seen -eq 0
-            test "$token" = "run"
+            contains -- "$token" run show
             and set run_seen 1
             set idx (math $idx + 1)
             continue
@@ -85,8 +85,49 @@ function __fish_rhask_should_complete_tasks_run
     return 0
 end
 
+function __fish_rhask_task_arg_candidates
+    string match -q -- '-*' (commandline -ct)
+    or return
+    set -l tokens (commandline -opc)
+    set -l total (count $tokens)
+    set -l file
+    set -l task
+    set -l idx 2
+    while test $idx -le $total
+        set token $tokens[$idx]
+        switch $token
+            case '-f' '--file'
+                set idx (math $idx + 1)
+                if test $idx -le $total
+                    set file $tokens[$idx]
+                end
+            case '-*'
+                ;
+            case 'run'
+                test -n "$task"
+                and break
+            case 'list' 'show' 'status' 'cache' 'completions' 'complete-tasks'
+                test -z "$task"
+                and return
+            case '*'
+                if test -z "$task"
+                    set task $token
+                end
+        end
+        set idx (math $idx + 1)
+    end
+    test -n "$task"
+    or return
+    set -l cmd rhask
+    if test -n "$file"
+        set cmd $cmd --file $file
+    end
+    $cmd complete-tasks --args $task 2>/dev/null
+end
+
 complete -c rhask -n "__fish_rhask_should_complete_tasks_direct" -a "(__fish_rhask_task_candidates)" -f
 complete -c rhask -n "__fish_rhask_should_complete_tasks_run" -a "(__fish_rhask_task_candidates)" -f
+complete -c rhask -a "(__fish_rhask_task_arg_candidates)" -f
 "#;
 
 pub fn patch(mut script: String) -> String {
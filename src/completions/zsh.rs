@@ -37,6 +37,29 @@ __rhask_dynamic_tasks() {
     "${cmd[@]}" 2>/dev/null
 }
 
+__rhask_task_args() {
+    local task="$1"
+    local file=""
+    local total=${#words[@]}
+    local idx=2
+    while (( idx <= total )); do
+        case "${words[idx]}" in
+            -f|--file)
+                ((idx++))
+                file="${words[idx]}"
+                ;;
+        esac
+        ((idx++))
+    done
+    local -a cmd
+    cmd=("rhask")
+    if [[ -n "$file" ]]; then
+        cmd+=("--file" "$file")
+    fi
+    cmd+=("complete-tasks" "--args" "$task")
+    "${cmd[@]}" 2>/dev/null
+}
+
 _rhask() {
     local cur="${words[CURRENT]}"
     local idx=2
@@ -77,7 +100,7 @@ _rhask() {
     local handled=0
     if (( CURRENT == first_idx )); then
         case "$cur" in
-            ""|-*|list|run|completions)
+            ""|-*|list|run|show|status|cache|completions)
                 ;;
             *)
                 local -a dynamic described
@@ -103,7 +126,7 @@ _rhask() {
                 fi
                 ;;
         esac
-    elif (( first_idx > 0 )) && [[ "${words[first_idx]}" = run ]] && (( CURRENT == first_idx + 1 )); then
+    elif (( first_idx > 0 )) && [[ "${words[first_idx]}" = run || "${words[first_idx]}" = show ]] && (( CURRENT == first_idx + 1 )); then
         local -a dynamic described
         local candidate
         local __rhask_prev_insert __rhask_prev_list
@@ -127,6 +150,32 @@ _rhask() {
         fi
     fi
 
+    local task_idx=$first_idx
+    if (( first_idx > 0 )) && [[ "${words[first_idx]}" = run ]]; then
+        task_idx=$((first_idx + 1))
+        while (( task_idx < CURRENT )) && [[ "${words[task_idx]}" = -* ]]; do
+            ((task_idx++))
+        done
+    fi
+    if (( ! handled && first_idx > 0 && CURRENT > task_idx )) && [[ "$cur" = -* ]]; then
+        case "${words[first_idx]}" in
+            list|show|status|cache|completions|complete-tasks)
+                ;;
+            *)
+                local -a arg_lines arg_described
+                local arg_line
+                arg_lines=( ${(f)"$(__rhask_task_args "${words[task_idx]}")"} )
+                arg_described=()
+                for arg_line in "${arg_lines[@]}"; do
+                    arg_described+=("${arg_line/$'\t'/:}")
+                done
+                if (( ${#arg_described[@]} )); then
+                    _describe -t rhask-task-args 'task argument' arg_described && handled=1
+                fi
+                ;;
+        esac
+    fi
+
     if (( handled )); then
         return 0
     fi
@@ -3,12 +3,13 @@ use rhai::{
     plugin::*, Array, Engine, EvalAltResult, FnPtr, ImmutableString, Map, NativeCallContext,
     Position,
 };
+use rhai_process::PipelineExecutor;
 
+use super::process::{resolve_program, Command, Executor, Pipe};
 use super::runtime::{
     array_to_positional, ensure_actions_scope, map_to_named, runtime_from_ctx, trigger_impl,
-    with_build_stack, ScopeGuard, ScopeKind,
+    with_build_stack, RuntimeHandle, ScopeGuard, ScopeKind,
 };
-use rhai_process::PipelineExecutor;
 use std::io::{self, Write};
 
 pub fn register(engine: &mut Engine) {
@@ -74,9 +75,48 @@ pub mod rhask_api {
         with_build_stack(&ctx, move |stack| stack.set_args(params))
     }
 
+    #[rhai_fn(global, name = "arg", return_raw)]
+    pub fn add_arg(ctx: NativeCallContext, name: &str) -> Result<(), Box<EvalAltResult>> {
+        with_build_stack(&ctx, |stack| stack.add_arg(name, Map::new()))
+    }
+
+    #[rhai_fn(global, name = "arg", return_raw)]
+    pub fn add_arg_with_options(
+        ctx: NativeCallContext,
+        name: &str,
+        options: Map,
+    ) -> Result<(), Box<EvalAltResult>> {
+        with_build_stack(&ctx, move |stack| stack.add_arg(name, options))
+    }
+
+    #[rhai_fn(global, name = "deps", return_raw)]
+    pub fn set_deps(ctx: NativeCallContext, deps: Array) -> Result<(), Box<EvalAltResult>> {
+        with_build_stack(&ctx, move |stack| stack.set_deps(deps))
+    }
+
+    #[rhai_fn(global, name = "sources", return_raw)]
+    pub fn set_sources(ctx: NativeCallContext, patterns: Array) -> Result<(), Box<EvalAltResult>> {
+        with_build_stack(&ctx, move |stack| stack.set_sources(patterns))
+    }
+
+    #[rhai_fn(global, name = "outputs", return_raw)]
+    pub fn set_outputs(ctx: NativeCallContext, patterns: Array) -> Result<(), Box<EvalAltResult>> {
+        with_build_stack(&ctx, move |stack| stack.set_outputs(patterns))
+    }
+
+    #[rhai_fn(global, name = "env", return_raw)]
+    pub fn set_env(ctx: NativeCallContext, vars: Map) -> Result<(), Box<EvalAltResult>> {
+        with_build_stack(&ctx, move |stack| stack.set_env(vars))
+    }
+
+    #[rhai_fn(global, name = "fingerprint", return_raw)]
+    pub fn enable_fingerprint(ctx: NativeCallContext) -> Result<(), Box<EvalAltResult>> {
+        with_build_stack(&ctx, |stack| stack.enable_fingerprint())
+    }
+
     #[rhai_fn(global, name = "trigger", return_raw)]
     pub fn trigger_simple(ctx: NativeCallContext, name: &str) -> Result<(), Box<EvalAltResult>> {
-        trigger_impl(&ctx, name, Vec::new(), IndexMap::new())
+        trigger_impl(&ctx, name, Vec::new(), IndexMap::new(), Vec::new())
     }
 
     #[rhai_fn(global, name = "trigger", return_raw)]
@@ -85,8 +125,8 @@ pub mod rhask_api {
         name: &str,
         positional: Array,
     ) -> Result<(), Box<EvalAltResult>> {
-        let positionals = array_to_positional(positional)?;
-        trigger_impl(&ctx, name, positionals, IndexMap::new())
+        let (positionals, passthrough) = array_to_positional(positional)?;
+        trigger_impl(&ctx, name, positionals, IndexMap::new(), passthrough)
     }
 
     #[rhai_fn(global, name = "trigger", return_raw)]
@@ -96,7 +136,7 @@ pub mod rhask_api {
         named: Map,
     ) -> Result<(), Box<EvalAltResult>> {
         let named_args = map_to_named(named)?;
-        trigger_impl(&ctx, name, Vec::new(), named_args)
+        trigger_impl(&ctx, name, Vec::new(), named_args, Vec::new())
     }
 
     #[rhai_fn(global, name = "trigger", return_raw)]
@@ -106,15 +146,134 @@ pub mod rhask_api {
         positional: Array,
         named: Map,
     ) -> Result<(), Box<EvalAltResult>> {
-        let positionals = array_to_positional(positional)?;
+        let (positionals, passthrough) = array_to_positional(positional)?;
         let named_args = map_to_named(named)?;
-        trigger_impl(&ctx, name, positionals, named_args)
+        trigger_impl(&ctx, name, positionals, named_args, passthrough)
+    }
+
+    #[rhai_fn(global, name = "cwd", return_raw)]
+    pub fn current_dir(ctx: NativeCallContext) -> Result<ImmutableString, Box<EvalAltResult>> {
+        let runtime = runtime_from_ctx(&ctx)?;
+        let dir = runtime.exec_state.lock().unwrap().effective_dir();
+        Ok(dir.to_string_lossy().into_owned().into())
+    }
+
+    #[rhai_fn(global, name = "passthrough", return_raw)]
+    pub fn passthrough_args(ctx: NativeCallContext) -> Result<Array, Box<EvalAltResult>> {
+        let runtime = runtime_from_ctx(&ctx)?;
+        ensure_actions_scope(&runtime.exec_state, "passthrough()")?;
+        let words = runtime.exec_state.lock().unwrap().current_passthrough();
+        Ok(words.into_iter().map(Into::into).collect())
+    }
+
+    #[rhai_fn(global, name = "cmd", return_raw)]
+    pub fn command(ctx: NativeCallContext, args: Array) -> Result<Command, Box<EvalAltResult>> {
+        build_command(&ctx, args)
+    }
+
+    #[rhai_fn(global, name = "env", return_raw)]
+    pub fn command_env(
+        ctx: NativeCallContext,
+        command: Command,
+        env: Map,
+    ) -> Result<Command, Box<EvalAltResult>> {
+        runtime_from_ctx(&ctx)?.process.with_env(command, env)
+    }
+
+    #[rhai_fn(global, name = "env_var", return_raw)]
+    pub fn command_env_var(
+        ctx: NativeCallContext,
+        command: Command,
+        key: ImmutableString,
+        value: ImmutableString,
+    ) -> Result<Command, Box<EvalAltResult>> {
+        runtime_from_ctx(&ctx)?
+            .process
+            .with_env_var(command, key, value)
+    }
+
+    #[rhai_fn(global, name = "pipe")]
+    pub fn command_pipe(command: Command, next: Command) -> Pipe {
+        command.pipe(next)
+    }
+
+    #[rhai_fn(global, name = "pipe")]
+    pub fn pipeline_pipe(pipeline: Pipe, next: Command) -> Pipe {
+        pipeline.pipe(next)
+    }
+
+    #[rhai_fn(global, name = "build")]
+    pub fn command_build(command: Command) -> Executor {
+        command.build()
+    }
+
+    #[rhai_fn(global, name = "build")]
+    pub fn pipeline_build(pipeline: Pipe) -> Executor {
+        pipeline.build()
+    }
+
+    #[rhai_fn(global, name = "cwd")]
+    pub fn executor_cwd(executor: Executor, path: ImmutableString) -> Executor {
+        executor.cwd(path.into())
+    }
+
+    #[rhai_fn(global, name = "timeout")]
+    pub fn executor_timeout(executor: Executor, timeout: rhai::INT) -> Executor {
+        executor.timeout(timeout)
+    }
+
+    #[rhai_fn(global, name = "allow_exit_codes")]
+    pub fn executor_exit_codes(executor: Executor, codes: Array) -> Executor {
+        executor.allow_exit_codes(codes)
+    }
+
+    #[rhai_fn(global, name = "run", return_raw)]
+    pub fn executor_run(
+        ctx: NativeCallContext,
+        executor: Executor,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        prepare_pipeline(executor, &runtime_from_ctx(&ctx)?)?.run()
+    }
+
+    #[rhai_fn(global, name = "run_stream", return_raw)]
+    pub fn executor_run_stream_default(
+        ctx: NativeCallContext,
+        executor: Executor,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        prepare_pipeline(executor, &runtime_from_ctx(&ctx)?)?.run_stream(&ctx, None, None)
+    }
+
+    #[rhai_fn(global, name = "run_stream", return_raw)]
+    pub fn executor_run_stream_stdout(
+        ctx: NativeCallContext,
+        executor: Executor,
+        stdout_cb: FnPtr,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        prepare_pipeline(executor, &runtime_from_ctx(&ctx)?)?.run_stream(
+            &ctx,
+            Some(stdout_cb),
+            None,
+        )
+    }
+
+    #[rhai_fn(global, name = "run_stream", return_raw)]
+    pub fn executor_run_stream_both(
+        ctx: NativeCallContext,
+        executor: Executor,
+        stdout_cb: FnPtr,
+        stderr_cb: FnPtr,
+    ) -> Result<Map, Box<EvalAltResult>> {
+        prepare_pipeline(executor, &runtime_from_ctx(&ctx)?)?.run_stream(
+            &ctx,
+            Some(stdout_cb),
+            Some(stderr_cb),
+        )
     }
 
     #[rhai_fn(global, name = "exec", return_raw)]
     pub fn exec_executor(
         ctx: NativeCallContext,
-        executor: PipelineExecutor,
+        executor: Executor,
     ) -> Result<Map, Box<EvalAltResult>> {
         run_executor(&ctx, executor, ExecMode::Run)
     }
@@ -122,7 +281,7 @@ pub mod rhask_api {
     #[rhai_fn(global, name = "exec_stream", return_raw)]
     pub fn exec_stream_default(
         ctx: NativeCallContext,
-        executor: PipelineExecutor,
+        executor: Executor,
     ) -> Result<Map, Box<EvalAltResult>> {
         run_executor(
             &ctx,
@@ -137,7 +296,7 @@ pub mod rhask_api {
     #[rhai_fn(global, name = "exec_stream", return_raw)]
     pub fn exec_stream_stdout(
         ctx: NativeCallContext,
-        executor: PipelineExecutor,
+        executor: Executor,
         stdout_cb: FnPtr,
     ) -> Result<Map, Box<EvalAltResult>> {
         run_executor(
@@ -153,7 +312,7 @@ pub mod rhask_api {
     #[rhai_fn(global, name = "exec_stream", return_raw)]
     pub fn exec_stream_both(
         ctx: NativeCallContext,
-        executor: PipelineExecutor,
+        executor: Executor,
         stdout_cb: FnPtr,
         stderr_cb: FnPtr,
     ) -> Result<Map, Box<EvalAltResult>> {
@@ -178,26 +337,94 @@ enum ExecMode {
 
 fn run_executor(
     ctx: &NativeCallContext,
-    executor: PipelineExecutor,
+    executor: Executor,
     mode: ExecMode,
 ) -> Result<Map, Box<EvalAltResult>> {
     let runtime = runtime_from_ctx(ctx)?;
     ensure_actions_scope(&runtime.exec_state, "exec()")?;
-    let executor = apply_working_dir(executor, &runtime)?;
-    match mode {
+    let command = executor.command_line();
+    let pipeline = prepare_pipeline(executor, &runtime)?;
+    let result = match mode {
         ExecMode::Run => {
-            let result = executor.run()?;
+            let result = pipeline.run()?;
             forward_streams(&result);
-            Ok(result)
+            result
         }
-        ExecMode::Stream { stdout, stderr } => executor.run_stream(ctx, stdout, stderr),
+        ExecMode::Stream { stdout, stderr } => pipeline.run_stream(ctx, stdout, stderr)?,
+    };
+    ensure_success(&runtime, &command, &result)?;
+    Ok(result)
+}
+
+/// Fails when the pipeline exited with a status that `allow_exit_codes()` did not permit.
+/// The status is recorded so rhask exits with it if the task fails because of this error.
+fn ensure_success(
+    runtime: &RuntimeHandle,
+    command: &str,
+    result: &Map,
+) -> Result<(), Box<EvalAltResult>> {
+    let success = result
+        .get("success")
+        .and_then(|value| value.as_bool().ok())
+        .unwrap_or(true);
+    if success {
+        return Ok(());
     }
+    let status = result.get("status").cloned().unwrap_or_default();
+    let message = format!("`{}` exited with status {}", command, status);
+    if let Ok(code) = status.as_int() {
+        let code = i32::try_from(code).unwrap_or(1);
+        runtime.exec_state.lock().unwrap().record_exec_failure(
+            message.clone(),
+            command.to_string(),
+            code,
+        );
+    }
+    Err(EvalAltResult::ErrorRuntime(message.into(), Position::NONE).into())
 }
 
-fn apply_working_dir(
-    executor: PipelineExecutor,
-    runtime: &super::runtime::RuntimeHandle,
+/// Creates the `cmd()` builder. The running task's `env()` is not applied here but when the
+/// pipeline runs (see `prepare_pipeline`), so a builder handed to another task picks up that
+/// task's environment, just as it picks up its `dir()`.
+fn build_command(ctx: &NativeCallContext, mut args: Array) -> Result<Command, Box<EvalAltResult>> {
+    let runtime = runtime_from_ctx(ctx)?;
+    let working_dir = runtime.exec_state.lock().unwrap().current_dir();
+    if let (Some(dir), Some(program)) = (working_dir, args.first_mut()) {
+        let resolved = program
+            .read_lock::<ImmutableString>()
+            .and_then(|name| resolve_program(&dir, &name));
+        if let Some(resolved) = resolved {
+            *program = resolved.into();
+        }
+    }
+    runtime.process.command(args)
+}
+
+/// Builds rhai-process' executor for the running task: its `dir()` as the working directory
+/// and its `env()` under whatever the builder set with `.env()`/`.env_var()`.
+fn prepare_pipeline(
+    executor: Executor,
+    runtime: &RuntimeHandle,
 ) -> Result<PipelineExecutor, Box<EvalAltResult>> {
+    let executor = apply_working_dir(executor, runtime)?;
+    let env: Map = {
+        let guard = runtime.exec_state.lock().unwrap();
+        guard
+            .current_env()
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect()
+    };
+    runtime.process.pipeline(executor, &env)
+}
+
+/// Points the pipeline at the running task's `dir()`. Like the task's `env()`, it is applied
+/// by `exec()` as well as the plain `run()`/`run_stream()` methods, which skip rhask's scope
+/// and exit-status checks.
+fn apply_working_dir(
+    executor: Executor,
+    runtime: &RuntimeHandle,
+) -> Result<Executor, Box<EvalAltResult>> {
     let working_dir = {
         let guard = runtime.exec_state.lock().unwrap();
         guard.current_dir()
@@ -205,11 +432,15 @@ fn apply_working_dir(
     if let Some(dir) = working_dir {
         let path = dir.to_str().map(|s| s.to_string()).ok_or_else(|| {
             EvalAltResult::ErrorRuntime(
-                format!("dir(): '{}' cannot be represented as UTF-8", dir.display()).into(),
+                format!(
+                    "Working directory '{}' cannot be represented as UTF-8",
+                    dir.display()
+                )
+                .into(),
                 Position::NONE,
             )
         })?;
-        executor.cwd(path)
+        Ok(executor.cwd(path))
     } else {
         Ok(executor)
     }
@@ -1,10 +1,11 @@
-use rhai::{packages::Package, Dynamic, Engine};
+use rhai::{Dynamic, Engine};
 use std::sync::{Arc, Mutex};
 
 use super::api;
 use super::core::ExecutionState;
+use super::process;
 use super::runtime::{BuildStackRef, RegistryRef, RuntimeHandle};
-use rhai_process::{Config, ProcessPackage};
+use rhai_process::Config;
 
 pub fn register_all(
     engine: &mut Engine,
@@ -12,8 +13,8 @@ pub fn register_all(
     exec_state: Arc<Mutex<ExecutionState>>,
     build_stack: BuildStackRef,
 ) {
-    let runtime = RuntimeHandle::new(registry, exec_state, build_stack);
+    let runtime = RuntimeHandle::new(registry, exec_state, build_stack, Config::default());
     engine.set_default_tag(Dynamic::from(runtime));
-    ProcessPackage::new(Config::default()).register_into_engine(engine);
+    process::register_types(engine);
     api::register(engine);
 }
@@ -1,35 +1,59 @@
-use rhai::{Dynamic, Engine, EvalAltResult, FnPtr, Position, AST};
+use indexmap::IndexMap;
+use rhai::module_resolvers::FileModuleResolver;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Position, AST};
+use std::collections::{HashMap, HashSet};
 use std::env;
-use std::io;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
 
 use super::bindings;
+use super::scheduler::{self, ScheduledTask};
+use crate::error::RhaskError;
 use crate::logger::*;
+use crate::printer;
 use crate::task::{
-    prepare_arguments_from_cli, BuildStack, ListRenderMode, TaskLookup, TaskRegistry,
+    prepare_arguments_from_cli, prepare_arguments_from_parts, split_passthrough, BuildStack,
+    EffectiveSettings, ExportFormat, ListRenderMode, NativeAction, ParameterSpec, PendingRun, Task,
+    TaskAction, TaskInfo, TaskLookup, TaskRegistry,
 };
 
+/// Nested `trigger()` calls allowed unless `--max-trigger-depth` says otherwise.
+pub const DEFAULT_MAX_TRIGGER_DEPTH: usize = 32;
+
 pub struct ScriptEngine {
     pub engine: Engine,
     pub registry: Arc<Mutex<TaskRegistry>>,
     pub ast: Option<AST>,
     pub(crate) exec_state: Arc<Mutex<ExecutionState>>,
     pub(crate) build_stack: Arc<Mutex<BuildStack>>,
+    pub(crate) cancel: Arc<AtomicBool>,
+    jobs: usize,
 }
 
 impl ScriptEngine {
     pub fn new() -> Self {
         let mut engine = Engine::new();
-        // Rhai's register_fn requires closures to be Send + Sync, so we use Arc<Mutex<_>>
-        // even though access is effectively single-threaded.
-        #[allow(clippy::arc_with_non_send_sync)]
         let registry = Arc::new(Mutex::new(TaskRegistry::new()));
         let exec_state = Arc::new(Mutex::new(ExecutionState::new()));
-        #[allow(clippy::arc_with_non_send_sync)]
         let build_stack = Arc::new(Mutex::new(BuildStack::new()));
+        let cancel = Arc::new(AtomicBool::new(false));
 
         engine.set_max_expr_depths(256, 128);
+        {
+            let cancel = cancel.clone();
+            engine.on_progress(move |_| {
+                if cancel.load(Ordering::Relaxed) {
+                    Some(Dynamic::UNIT)
+                } else {
+                    None
+                }
+            });
+        }
 
         bindings::register_all(
             &mut engine,
@@ -44,110 +68,353 @@ impl ScriptEngine {
             ast: None,
             exec_state,
             build_stack,
+            cancel,
+            jobs: 1,
         }
     }
 
-    pub fn run_script(&mut self, path: &str) -> Result<(), Box<EvalAltResult>> {
+    pub fn run_script(&mut self, path: &str) -> Result<(), RhaskError> {
         let script_path = resolve_script_path(path).map_err(|err| -> Box<EvalAltResult> {
             Box::new(EvalAltResult::ErrorRuntime(
                 format!("Unable to locate script file '{}': {}", path, err).into(),
                 Position::NONE,
             ))
         })?;
-        {
-            let mut stack = self.build_stack.lock().unwrap();
-            stack.reset();
-            let parent = script_path
-                .parent()
-                .map(Path::to_path_buf)
-                .unwrap_or_else(|| PathBuf::from("."));
-            stack.set_script_root(parent);
-        }
+        let root = script_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."));
 
         debug!("run_script({})", script_path.display());
 
+        // Imports resolve next to the importing file, even after a load_source().
+        self.engine.set_module_resolver(FileModuleResolver::new());
         let ast = self.engine.compile_file(script_path)?;
         trace!("run_script: AST compiled successfully");
+        self.load_ast(ast, root, None)
+    }
+
+    /// Compiles `source` from memory and registers its tasks like [`Self::run_script`] does
+    /// for a file. Relative paths (`dir()`, `sources()`, imports, the cache) resolve against
+    /// `root`.
+    pub fn load_source(&mut self, source: &str, root: &Path) -> Result<(), RhaskError> {
+        debug!(
+            "load_source({} bytes, root={})",
+            source.len(),
+            root.display()
+        );
+
+        self.engine
+            .set_module_resolver(FileModuleResolver::new_with_path(root));
+        let ast = self
+            .engine
+            .compile(source)
+            .map_err(Box::<EvalAltResult>::from)?;
+        trace!("load_source: AST compiled successfully");
+        self.load_ast(ast, root.to_path_buf(), Some(source.to_string()))
+    }
+
+    /// Reads a script from `reader`, then behaves like [`Self::load_source`].
+    pub fn load_reader(&mut self, mut reader: impl Read, root: &Path) -> Result<(), RhaskError> {
+        let mut source = String::new();
+        reader
+            .read_to_string(&mut source)
+            .map_err(|err| user_error(format!("Unable to read script: {}", err)))?;
+        self.load_source(&source, root)
+    }
+
+    fn load_ast(
+        &mut self,
+        ast: AST,
+        root: PathBuf,
+        inline_source: Option<String>,
+    ) -> Result<(), RhaskError> {
+        {
+            let mut stack = self.build_stack.lock().unwrap();
+            stack.reset();
+            self.exec_state.lock().unwrap().script_root = Some(root.clone());
+            stack.set_script_root(root);
+            if let Some(source) = inline_source {
+                stack.set_inline_source(source);
+            }
+        }
+
         self.engine.run_ast(&ast)?;
-        trace!("run_script: AST executed successfully");
+        trace!("load_ast: AST executed successfully");
+        self.registry.lock().unwrap().validate_dependencies()?;
+        trace!("load_ast: task dependencies validated");
         self.ast = Some(ast);
         Ok(())
     }
 
     pub fn list_tasks(&self, group: Option<&str>, flat: bool) {
-        let mode = if flat {
-            ListRenderMode::Flat
-        } else {
-            ListRenderMode::Tree
+        self.registry.lock().unwrap().list(group, render_mode(flat));
+    }
+
+    /// Prints the task tree (or one group of it) as JSON, YAML or TSV.
+    pub fn export_tasks(&self, group: Option<&str>, format: ExportFormat) {
+        self.registry.lock().unwrap().export(group, format);
+    }
+
+    pub fn list_task_settings(&self, group: Option<&str>, flat: bool) {
+        let script_root = self.exec_state.lock().unwrap().script_root.clone();
+        self.registry.lock().unwrap().list_settings(
+            group,
+            render_mode(flat),
+            script_root.as_deref(),
+        );
+    }
+
+    pub fn show_status(&self, group: Option<&str>) {
+        let (script_root, cache_dir) = {
+            let state = self.exec_state.lock().unwrap();
+            (state.script_root.clone(), state.cache_dir())
         };
-        self.registry.lock().unwrap().list(group, mode);
+        self.registry
+            .lock()
+            .unwrap()
+            .status(group, script_root.as_deref(), cache_dir.as_deref());
     }
 
-    pub fn run_task(&self, name: &str, raw_args: &[String]) -> Result<(), Box<EvalAltResult>> {
+    /// Prints the details of a single task (`rhask show`).
+    pub fn show_task(&self, name: &str) -> Result<(), RhaskError> {
+        let full_path = self.resolve_task_name(name)?;
+        let script_root = self.exec_state.lock().unwrap().script_root.clone();
+        self.registry
+            .lock()
+            .unwrap()
+            .show(&full_path, script_root.as_deref());
+        Ok(())
+    }
+
+    pub fn run_task(&self, name: &str, raw_args: &[String]) -> Result<(), RhaskError> {
         debug!("run_task({})", name);
+        let (raw_args, passthrough) = split_passthrough(raw_args);
 
-        let lookup = {
+        let full_path = self.resolve_task_name(name)?;
+        let (call_args, func, settings) = {
             let reg = self.registry.lock().unwrap();
-            reg.resolve_task(name)
+            let args =
+                prepare_arguments_from_cli(&reg, &full_path, raw_args).map_err(|source| {
+                    RhaskError::ArgumentError {
+                        task: full_path.clone(),
+                        source,
+                    }
+                })?;
+            trace!(
+                "run_task: resolved '{}' -> '{}', args_len={}, raw_args={:?}",
+                name,
+                full_path,
+                args.len(),
+                raw_args
+            );
+            let task_meta = reg.task(&full_path);
+            let task_actions = task_meta.and_then(|task| task.actions.clone());
+            let mut settings = reg.effective_settings(&full_path);
+            settings.passthrough = passthrough;
+            (args, task_actions, settings)
         };
 
-        let (full_path, call_args, func, task_dir) = match lookup {
-            TaskLookup::Found { full_path } => {
-                let (call_args, actions, working_dir) = {
-                    let reg = self.registry.lock().unwrap();
-                    let args = prepare_arguments_from_cli(&reg, &full_path, raw_args)?;
-                    trace!(
-                        "run_task: resolved '{}' -> '{}', args_len={}, raw_args={:?}",
-                        name,
-                        full_path,
-                        args.len(),
-                        raw_args
-                    );
-                    let task_meta = reg.task(&full_path);
-                    let task_actions = task_meta.and_then(|task| task.actions.clone());
-                    let working_dir = task_meta.and_then(|task| task.working_dir.clone());
-                    (args, task_actions, working_dir)
-                };
-                (full_path, call_args, actions, working_dir)
-            }
-            TaskLookup::NotFound => {
-                warn!("run_task: '{}' not found", name);
-                return Err(user_error(format!("Task '{}' does not exist.", name)));
-            }
-            TaskLookup::Ambiguous(candidates) => {
-                warn!("run_task: '{}' ambiguous matches {:?}", name, candidates);
-                let mut message = format!("Task '{}' matches multiple candidates:\n", name);
-                for candidate in candidates {
-                    message.push_str(&format!("  - {}\n", candidate));
+        self.exec_state.lock().unwrap().begin_invocation();
+        let order = {
+            let reg = self.registry.lock().unwrap();
+            reg.dependency_order(&full_path)?
+        };
+
+        if func.is_none() && order.is_empty() {
+            warn!("run_task: '{}' has no actions registered", full_path);
+            return Err(
+                user_error(format!("Task '{}' has no actions() registered.", full_path)).into(),
+            );
+        }
+
+        let mut scheduled = order
+            .iter()
+            .map(|dep| self.schedule_dependency(dep))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Native tasks run without a script, so only Rhai actions need the AST.
+        let empty_ast;
+        let ast = match &self.ast {
+            Some(ast) => ast,
+            None => {
+                let needs_script = scheduled
+                    .iter()
+                    .filter_map(|dep| dep.actions.as_ref())
+                    .chain(func.as_ref())
+                    .any(|action| matches!(action, TaskAction::Script(_)));
+                if needs_script {
+                    error!("run_task: AST not loaded before executing '{}'", full_path);
+                    return Err(user_error("AST is not loaded. Run the script first.").into());
                 }
-                message.push_str("Please use the fully-qualified name (e.g. group.task).");
-                return Err(user_error(message));
+                empty_ast = AST::empty();
+                &empty_ast
             }
         };
 
-        if let Some(ast) = &self.ast {
-            if let Some(func) = func {
-                let _scope = ActionScope::start(self.exec_state.clone(), task_dir)?;
-                trace!(
-                    "run_task: invoking actions for '{}' with {} argument(s)",
-                    full_path,
-                    call_args.len()
+        if self.jobs > 1 && !scheduled.is_empty() {
+            let deps = {
+                let reg = self.registry.lock().unwrap();
+                reg.direct_dependencies(&full_path)?
+            };
+            scheduled.push(ScheduledTask {
+                full_path,
+                deps,
+                args: call_args,
+                actions: func,
+                settings,
+            });
+            let (summary, result) = scheduler::run_parallel(self, ast, scheduled, self.jobs);
+            printer::print_run_summary(&summary);
+            return result.map_err(Into::into);
+        }
+
+        for dep in scheduled {
+            if !self
+                .exec_state
+                .lock()
+                .unwrap()
+                .mark_completed(&dep.full_path)
+            {
+                trace!("run_task: dependency '{}' already completed", dep.full_path);
+                continue;
+            }
+            if let Some(func) = dep.actions {
+                let Some(pending) = self.plan_run(&dep.full_path, &dep.args) else {
+                    continue;
+                };
+                debug!(
+                    "run_task: running dependency '{}' of '{}'",
+                    dep.full_path, full_path
                 );
-                self.invoke_action(ast, func, call_args)?;
-            } else {
-                warn!("run_task: '{}' has no actions registered", full_path);
-                return Err(user_error(format!(
-                    "Task '{}' has no actions() registered.",
-                    full_path
-                )));
+                let scope =
+                    ActionScope::start(self.exec_state.clone(), &dep.full_path, dep.settings)?;
+                scope.run(|| self.invoke_action(ast, &func, dep.args))?;
+                pending.complete();
             }
-        } else {
-            error!("run_task: AST not loaded before executing '{}'", full_path);
-            return Err(user_error("AST is not loaded. Run the script first."));
         }
+        self.exec_state.lock().unwrap().mark_completed(&full_path);
+
+        if let Some(func) = func {
+            let run_args = run_arguments(&call_args, &settings.passthrough);
+            let Some(pending) = self.plan_run(&full_path, &run_args) else {
+                return Ok(());
+            };
+            let scope = ActionScope::start(self.exec_state.clone(), &full_path, settings)?;
+            trace!(
+                "run_task: invoking actions for '{}' with {} argument(s)",
+                full_path,
+                call_args.len()
+            );
+            scope.run(|| self.invoke_action(ast, &func, call_args))?;
+            pending.complete();
+        }
+        Ok(())
+    }
+
+    fn schedule_dependency(&self, full_path: &str) -> Result<ScheduledTask, Box<EvalAltResult>> {
+        let reg = self.registry.lock().unwrap();
+        let args = prepare_arguments_from_parts(&reg, full_path, Vec::new(), IndexMap::new())?;
+        let deps = reg.direct_dependencies(full_path)?;
+        let task_meta = reg.task(full_path);
+        Ok(ScheduledTask {
+            full_path: full_path.to_string(),
+            deps,
+            args,
+            actions: task_meta.and_then(|task| task.actions.clone()),
+            settings: reg.effective_settings(full_path),
+        })
+    }
+
+    /// Registers a task implemented in Rust under `full_path` (e.g. `"db.migrate"`). It is
+    /// listed, completed, resolved by its short name and run like tasks from the rhaskfile,
+    /// including through `trigger()`. Missing parent groups are created; call this after
+    /// loading the script so its own groups keep their settings.
+    pub fn register_native_task<F>(
+        &mut self,
+        full_path: &str,
+        params: Vec<ParameterSpec>,
+        action: F,
+    ) -> Result<(), RhaskError>
+    where
+        F: Fn(&[Dynamic]) -> Result<(), Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+    {
+        debug!("register_native_task({})", full_path);
+        let task = Task {
+            actions: Some(TaskAction::Native(Arc::new(action))),
+            params,
+            ..Task::default()
+        };
+        self.registry
+            .lock()
+            .unwrap()
+            .insert_native_task(full_path, task)?;
         Ok(())
     }
 
+    pub fn set_jobs(&mut self, jobs: usize) {
+        self.jobs = jobs.max(1);
+    }
+
+    /// Maximum number of nested `trigger()` calls before a run is aborted.
+    pub fn set_max_trigger_depth(&mut self, depth: usize) {
+        self.exec_state.lock().unwrap().max_trigger_depth = depth.max(1);
+    }
+
+    /// Run tasks even when their `outputs()` are newer than their `sources()`.
+    pub fn set_force(&mut self, force: bool) {
+        self.exec_state.lock().unwrap().force = force;
+    }
+
+    /// Resolves `name` the way `rhask run` does: a full path, or trailing path segments
+    /// (such as `deploy` or `release.deploy`) that match exactly one task.
+    pub fn resolve(&self, name: &str) -> TaskLookup {
+        self.registry.lock().unwrap().resolve_task(name)
+    }
+
+    /// Everything known about the task or group at `full_path`.
+    pub fn task_info(&self, full_path: &str) -> Option<TaskInfo> {
+        self.registry.lock().unwrap().info(full_path)
+    }
+
+    /// Every task and group, depth-first in declaration order.
+    pub fn tasks(&self) -> Vec<TaskInfo> {
+        self.registry.lock().unwrap().entries()
+    }
+
+    fn resolve_task_name(&self, name: &str) -> Result<String, RhaskError> {
+        match self.resolve(name) {
+            TaskLookup::Found { full_path } => Ok(full_path),
+            TaskLookup::NotFound => {
+                warn!("resolve_task_name: '{}' not found", name);
+                Err(RhaskError::TaskNotFound {
+                    name: name.to_string(),
+                })
+            }
+            TaskLookup::Ambiguous(candidates) => {
+                warn!(
+                    "resolve_task_name: '{}' ambiguous matches {:?}",
+                    name, candidates
+                );
+                Err(RhaskError::Ambiguous {
+                    name: name.to_string(),
+                    candidates,
+                })
+            }
+        }
+    }
+
+    pub(super) fn plan_run(&self, full_path: &str, args: &[Dynamic]) -> Option<PendingRun> {
+        let (force, cache_dir) = {
+            let state = self.exec_state.lock().unwrap();
+            (state.force(), state.cache_dir())
+        };
+        self.registry
+            .lock()
+            .unwrap()
+            .plan_run(full_path, args, force, cache_dir.as_deref())
+    }
+
     pub fn default_task(&self) -> Option<String> {
         self.registry
             .lock()
@@ -162,104 +429,237 @@ impl Default for ScriptEngine {
     }
 }
 
+/// Per-thread action stacks so tasks running on different workers never share a context.
+/// The process working directory is never changed; each context records the directory
+/// its actions run in, and every process spawn applies it explicitly.
 pub(crate) struct ExecutionState {
-    contexts: Vec<ActionContext>,
+    contexts: HashMap<ThreadId, Vec<ActionContext>>,
+    /// Task chain of the innermost failed action, per thread, until the outermost scope
+    /// reports it.
+    failures: HashMap<ThreadId, Vec<String>>,
+    /// Error message, command line and exit status of the last command that failed `exec()`,
+    /// per thread.
+    exec_failures: HashMap<ThreadId, (String, String, i32)>,
+    completed: HashSet<String>,
     base_dir: PathBuf,
+    force: bool,
+    script_root: Option<PathBuf>,
+    max_trigger_depth: usize,
 }
 
 impl ExecutionState {
     fn new() -> Self {
         let base_dir = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
         Self {
-            contexts: Vec::new(),
+            contexts: HashMap::new(),
+            failures: HashMap::new(),
+            exec_failures: HashMap::new(),
+            completed: HashSet::new(),
             base_dir,
+            force: false,
+            script_root: None,
+            max_trigger_depth: DEFAULT_MAX_TRIGGER_DEPTH,
         }
     }
 
-    fn push(&mut self, working_dir: Option<PathBuf>) {
-        self.contexts.push(ActionContext::new(working_dir));
+    fn begin_invocation(&mut self) {
+        self.completed.clear();
+        self.failures.clear();
+        self.exec_failures.clear();
     }
 
-    fn pop(&mut self) {
-        self.contexts.pop();
+    /// Records `full_path` as run for the current invocation; returns `false` if it already was.
+    pub(crate) fn mark_completed(&mut self, full_path: &str) -> bool {
+        self.completed.insert(full_path.to_string())
     }
 
-    fn enter_directory(
-        &self,
-        working_dir: Option<&PathBuf>,
-    ) -> Result<Option<PathBuf>, Box<EvalAltResult>> {
-        let target_dir = working_dir
-            .cloned()
+    /// Whether up-to-date tasks should run anyway (`--force`).
+    pub(crate) fn force(&self) -> bool {
+        self.force
+    }
+
+    /// `.rhask/cache` next to the loaded rhaskfile.
+    pub(crate) fn cache_dir(&self) -> Option<PathBuf> {
+        self.script_root.as_deref().map(cache_dir_for)
+    }
+
+    fn push(&mut self, task: &str, settings: EffectiveSettings) {
+        let working_dir = settings
+            .working_dir
             .unwrap_or_else(|| self.base_dir.clone());
-        let previous_dir = env::current_dir().map_err(|err| {
-            user_error(format!("Failed to read current directory: {}", err))
-        })?;
-        if previous_dir != target_dir {
-            env::set_current_dir(&target_dir).map_err(|err| {
-                user_error(format!(
-                    "Failed to change working directory to '{}': {}",
-                    target_dir.display(),
-                    err
-                ))
-            })?;
+        let id = thread::current().id();
+        if !self.is_active() {
+            self.failures.remove(&id);
+            self.exec_failures.remove(&id);
         }
-        Ok(Some(previous_dir))
+        self.contexts.entry(id).or_default().push(ActionContext {
+            task: task.to_string(),
+            working_dir,
+            env: settings.env,
+            passthrough: settings.passthrough,
+        });
+    }
+
+    fn pop(&mut self) {
+        let id = thread::current().id();
+        if let Some(stack) = self.contexts.get_mut(&id) {
+            stack.pop();
+            if stack.is_empty() {
+                self.contexts.remove(&id);
+            }
+        }
+    }
+
+    fn current_stack(&self) -> Option<&Vec<ActionContext>> {
+        self.contexts.get(&thread::current().id())
     }
 
     pub(crate) fn is_active(&self) -> bool {
-        !self.contexts.is_empty()
+        self.current_stack().is_some_and(|stack| !stack.is_empty())
     }
 
+    /// Directory of the innermost running action, or `None` outside `actions()`.
     pub(crate) fn current_dir(&self) -> Option<PathBuf> {
-        self.contexts.last().and_then(|ctx| ctx.working_dir.clone())
+        self.current_stack()
+            .and_then(|stack| stack.last())
+            .map(|ctx| ctx.working_dir.clone())
+    }
+
+    /// Environment variables of the innermost running action.
+    pub(crate) fn current_env(&self) -> IndexMap<String, String> {
+        self.current_stack()
+            .and_then(|stack| stack.last())
+            .map(|ctx| ctx.env.clone())
+            .unwrap_or_default()
+    }
+
+    /// Words given after `--` to the innermost running action.
+    pub(crate) fn current_passthrough(&self) -> Vec<String> {
+        self.current_stack()
+            .and_then(|stack| stack.last())
+            .map(|ctx| ctx.passthrough.clone())
+            .unwrap_or_default()
+    }
+
+    /// Full paths of the tasks whose actions are running on this thread, outermost first.
+    pub(crate) fn task_chain(&self) -> Vec<String> {
+        self.current_stack()
+            .map(|stack| stack.iter().map(|ctx| ctx.task.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Rejects starting `task` from the running actions when it is already running on this
+    /// thread, or when that would nest more than `max_trigger_depth` trigger() calls.
+    fn ensure_can_nest(&self, task: &str) -> Result<(), Box<EvalAltResult>> {
+        let mut chain = self.task_chain();
+        if chain.iter().any(|running| running == task) {
+            chain.push(task.to_string());
+            return Err(user_error(format!(
+                "trigger cycle detected: {}",
+                chain.join(" -> ")
+            )));
+        }
+        if chain.len() > self.max_trigger_depth {
+            chain.push(task.to_string());
+            return Err(user_error(format!(
+                "trigger depth limit of {} exceeded: {}",
+                self.max_trigger_depth,
+                chain.join(" -> ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Remembers the command behind an `exec()` error so a task failing with that error
+    /// makes rhask exit with the command's status.
+    pub(crate) fn record_exec_failure(&mut self, message: String, command: String, status: i32) {
+        self.exec_failures
+            .insert(thread::current().id(), (message, command, status));
+    }
+
+    /// Remembers the task chain of the innermost failing action. Once the outermost action
+    /// fails, the error is prefixed with that chain, e.g. `release -> build.release: ...`.
+    fn annotate_failure(&mut self, err: Box<EvalAltResult>) -> Box<EvalAltResult> {
+        let id = thread::current().id();
+        let chain = self.task_chain();
+        let depth = chain.len();
+        self.failures.entry(id).or_insert(chain);
+        if depth > 1 {
+            return err;
+        }
+        let chain = self.failures.remove(&id).unwrap_or_default();
+        let exec_failure = self.exec_failures.remove(&id);
+        match err.unwrap_inner() {
+            EvalAltResult::ErrorTerminated(..) => err,
+            EvalAltResult::ErrorRuntime(value, position) => match exec_failure {
+                Some((message, command, status)) if message == value.to_string() => {
+                    RhaskError::ExecFailed {
+                        status,
+                        command,
+                        chain,
+                        source: err,
+                    }
+                    .into()
+                }
+                _ if position.is_none() => user_error(format!("{}: {}", chain.join(" -> "), value)),
+                _ => user_error(format!("{}: {} ({})", chain.join(" -> "), value, position)),
+            },
+            inner => user_error(format!("{}: {}", chain.join(" -> "), inner)),
+        }
+    }
+
+    /// Directory processes spawned right now would run in.
+    pub(crate) fn effective_dir(&self) -> PathBuf {
+        self.current_dir().unwrap_or_else(|| self.base_dir.clone())
     }
 }
 
 #[derive(Clone, Debug)]
 struct ActionContext {
-    working_dir: Option<PathBuf>,
-}
-
-impl ActionContext {
-    fn new(working_dir: Option<PathBuf>) -> Self {
-        Self { working_dir }
-    }
+    task: String,
+    working_dir: PathBuf,
+    env: IndexMap<String, String>,
+    passthrough: Vec<String>,
 }
 
 pub(crate) struct ActionScope {
     state: Arc<Mutex<ExecutionState>>,
-    previous_dir: Option<PathBuf>,
 }
 
 impl ActionScope {
     pub(crate) fn start(
         state: Arc<Mutex<ExecutionState>>,
-        working_dir: Option<PathBuf>,
+        task: &str,
+        settings: EffectiveSettings,
     ) -> Result<Self, Box<EvalAltResult>> {
-        let previous_dir = {
-            let mut guard = state.lock().unwrap();
-            let previous = guard.enter_directory(working_dir.as_ref())?;
-            guard.push(working_dir);
-            previous
-        };
-        Ok(Self { state, previous_dir })
+        state.lock().unwrap().push(task, settings);
+        Ok(Self { state })
     }
 
     pub(crate) fn start_nested(
         state: Arc<Mutex<ExecutionState>>,
         label: &str,
-        working_dir: Option<PathBuf>,
+        task: &str,
+        settings: EffectiveSettings,
     ) -> Result<Self, Box<EvalAltResult>> {
-        let previous_dir = {
+        {
             let mut guard = state.lock().unwrap();
             if !guard.is_active() {
                 return Err(actions_only_error(label));
             }
-            let previous = guard.enter_directory(working_dir.as_ref())?;
-            guard.push(working_dir);
-            previous
-        };
-        Ok(Self { state, previous_dir })
+            guard.ensure_can_nest(task)?;
+            guard.push(task, settings);
+        }
+        Ok(Self { state })
+    }
+
+    /// Runs a task's actions inside this scope, naming the chain of running tasks in the
+    /// error if they fail.
+    pub(crate) fn run<T>(
+        &self,
+        action: impl FnOnce() -> Result<T, Box<EvalAltResult>>,
+    ) -> Result<T, Box<EvalAltResult>> {
+        action().map_err(|err| self.state.lock().unwrap().annotate_failure(err))
     }
 }
 
@@ -267,11 +667,6 @@ impl Drop for ActionScope {
     fn drop(&mut self) {
         let mut guard = self.state.lock().unwrap();
         guard.pop();
-        if let Some(prev) = self.previous_dir.take() {
-            if let Err(err) = env::set_current_dir(&prev) {
-                warn!("Failed to restore working directory: {}", err);
-            }
-        }
     }
 }
 
@@ -284,17 +679,26 @@ pub(crate) fn actions_only_error(label: &str) -> Box<EvalAltResult> {
 }
 
 impl ScriptEngine {
-    fn invoke_action(
+    pub(super) fn invoke_action(
         &self,
         ast: &AST,
-        func: FnPtr,
+        action: &TaskAction,
         args: Vec<Dynamic>,
     ) -> Result<(), Box<EvalAltResult>> {
-        let _ = func.call::<Dynamic>(&self.engine, ast, args)?;
-        Ok(())
+        match action {
+            TaskAction::Script(func) => {
+                let _ = func.call::<Dynamic>(&self.engine, ast, args)?;
+                Ok(())
+            }
+            TaskAction::Native(func) => call_native(func, &args),
+        }
     }
 }
 
+pub(super) fn call_native(func: &NativeAction, args: &[Dynamic]) -> Result<(), Box<EvalAltResult>> {
+    func(args).map_err(|err| user_error(err.to_string()))
+}
+
 pub(crate) fn user_error(message: impl Into<String>) -> Box<EvalAltResult> {
     Box::new(EvalAltResult::ErrorRuntime(
         message.into().into(),
@@ -302,6 +706,52 @@ pub(crate) fn user_error(message: impl Into<String>) -> Box<EvalAltResult> {
     ))
 }
 
+/// Arguments a run is fingerprinted with: pass-through words count as one extra array.
+pub(super) fn run_arguments(args: &[Dynamic], passthrough: &[String]) -> Vec<Dynamic> {
+    let mut all = args.to_vec();
+    if !passthrough.is_empty() {
+        let words: Array = passthrough.iter().cloned().map(Dynamic::from).collect();
+        all.push(words.into());
+    }
+    all
+}
+
+fn render_mode(flat: bool) -> ListRenderMode {
+    if flat {
+        ListRenderMode::Flat
+    } else {
+        ListRenderMode::Tree
+    }
+}
+
+fn cache_dir_for(script_root: &Path) -> PathBuf {
+    script_root.join(".rhask").join("cache")
+}
+
+/// Removes the fingerprint cache that belongs to the rhaskfile found from `path`.
+pub fn clean_cache(path: &str) -> Result<(), RhaskError> {
+    let script_path = resolve_script_path(path)
+        .map_err(|err| user_error(format!("Unable to locate script file '{}': {}", path, err)))?;
+    let root = script_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let cache_dir = cache_dir_for(&root);
+    if !cache_dir.exists() {
+        printer::info(format!("No cache found at {}", cache_dir.display()));
+        return Ok(());
+    }
+    fs::remove_dir_all(&cache_dir).map_err(|err| {
+        user_error(format!(
+            "Failed to remove cache '{}': {}",
+            cache_dir.display(),
+            err
+        ))
+    })?;
+    printer::info(format!("Removed {}", cache_dir.display()));
+    Ok(())
+}
+
 fn resolve_script_path(path: &str) -> io::Result<PathBuf> {
     let candidate = Path::new(path);
     if candidate.is_absolute() {
@@ -338,6 +788,7 @@ fn resolve_script_path(path: &str) -> io::Result<PathBuf> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::task::ParamType;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -361,6 +812,7 @@ mod tests {
             .run_script(script.path().to_str().unwrap())
             .expect("load script");
         let err = engine.run_task("unknown_task", &[]).unwrap_err();
+        assert!(matches!(&err, RhaskError::TaskNotFound { name } if name == "unknown_task"));
         assert!(
             err.to_string()
                 .contains("Task 'unknown_task' does not exist."),
@@ -390,6 +842,10 @@ mod tests {
             .run_script(script.path().to_str().unwrap())
             .expect("load script");
         let err = engine.run_task("deploy", &[]).unwrap_err();
+        assert!(matches!(
+            &err,
+            RhaskError::Ambiguous { candidates, .. } if candidates.len() == 2
+        ));
         assert!(
             err.to_string().contains("matches multiple candidates"),
             "unexpected error message: {}",
@@ -397,6 +853,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn run_task_stops_trigger_cycles_and_deep_chains() {
+        let script = write_script(
+            r#"
+            task("ping", || { actions(|| { trigger("pong"); }); });
+            task("pong", || { actions(|| { trigger("ping"); }); });
+            task("outer", || { actions(|| { trigger("middle"); }); });
+            task("middle", || { actions(|| { trigger("inner"); }); });
+            task("inner", || { actions(|| {}); });
+        "#,
+        );
+        let mut engine = ScriptEngine::new();
+        engine
+            .run_script(script.path().to_str().unwrap())
+            .expect("load script");
+        let err = engine.run_task("ping", &[]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("trigger cycle detected: ping -> pong -> ping"),
+            "unexpected error message: {}",
+            err
+        );
+
+        engine.run_task("outer", &[]).expect("depth within limit");
+        engine.set_max_trigger_depth(1);
+        let err = engine.run_task("outer", &[]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("trigger depth limit of 1 exceeded: outer -> middle -> inner"),
+            "unexpected error message: {}",
+            err
+        );
+    }
+
     #[test]
     fn run_task_errors_when_actions_missing() {
         let script = write_script(
@@ -613,6 +1103,255 @@ mod tests {
             .expect("trigger with mixed args");
     }
 
+    #[test]
+    fn run_script_rejects_dependency_cycles() {
+        let script = write_script(
+            r#"
+            task("a", || {
+                deps(["b"]);
+                actions(|| {});
+            });
+            task("b", || {
+                deps(["a"]);
+                actions(|| {});
+            });
+        "#,
+        );
+        let mut engine = ScriptEngine::new();
+        let err = engine
+            .run_script(script.path().to_str().unwrap())
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Dependency cycle detected: a -> b -> a"),
+            "unexpected error message: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn load_reader_registers_tasks_relative_to_root() {
+        let root = tempfile::tempdir().expect("temp dir");
+        fs::write(
+            root.path().join("names.rhai"),
+            r#"export const GREET = "greet";"#,
+        )
+        .expect("write module");
+        let source = r#"
+import "names" as names;
+task(names::GREET, || {
+    actions(|| { print("hello"); });
+});
+"#;
+        let mut engine = ScriptEngine::new();
+        engine
+            .load_reader(source.as_bytes(), root.path())
+            .expect("load script from memory");
+        {
+            let registry = engine.registry.lock().unwrap();
+            let task = registry.task("greet").expect("task registered");
+            assert_eq!(
+                task.declared_at.as_ref().map(ToString::to_string),
+                Some("line 3, column 1".to_string())
+            );
+            assert!(task
+                .source_text
+                .as_deref()
+                .is_some_and(|text| text.starts_with("task(names::GREET")));
+        }
+        assert_eq!(
+            engine.exec_state.lock().unwrap().cache_dir(),
+            Some(root.path().join(".rhask/cache"))
+        );
+        engine.run_task("greet", &[]).expect("run task");
+
+        let err = engine
+            .load_source("task(\"broken\", || {", root.path())
+            .unwrap_err();
+        assert!(matches!(err, RhaskError::ScriptError { .. }));
+    }
+
+    #[test]
+    fn native_tasks_run_from_cli_and_trigger() {
+        let script = write_script(
+            r#"
+            group("db", || {
+                description("database helpers");
+            });
+            task("reset", || {
+                actions(|| { trigger("migrate", ["down"]); });
+            });
+        "#,
+        );
+        let mut engine = ScriptEngine::new();
+        engine
+            .run_script(script.path().to_str().unwrap())
+            .expect("load script");
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let seen = calls.clone();
+        let direction = ParameterSpec::new(
+            "direction".to_string(),
+            ParamType::String,
+            Some("up".into()),
+        );
+        engine
+            .register_native_task("db.migrate", vec![direction], move |args| {
+                seen.lock().unwrap().push(args[0].to_string());
+                Ok(())
+            })
+            .expect("register native task");
+        engine
+            .register_native_task("tools.fail", Vec::new(), |_| Err("no database".into()))
+            .expect("register native task with new group");
+
+        engine.run_task("migrate", &[]).expect("run by short name");
+        engine
+            .run_task("reset", &[])
+            .expect("run through trigger()");
+        assert_eq!(*calls.lock().unwrap(), ["up", "down"]);
+
+        let err = engine.run_task("tools.fail", &[]).unwrap_err();
+        assert!(
+            err.to_string().contains("tools.fail: no database"),
+            "{}",
+            err
+        );
+
+        let paths: Vec<String> = engine
+            .tasks()
+            .into_iter()
+            .map(|info| info.full_path)
+            .collect();
+        assert_eq!(paths, ["db", "db.migrate", "reset", "tools", "tools.fail"]);
+        assert!(engine
+            .register_native_task("reset", Vec::new(), |_| Ok(()))
+            .is_err());
+    }
+
+    #[test]
+    fn native_tasks_run_without_a_loaded_script() {
+        let mut engine = ScriptEngine::new();
+        let ran = Arc::new(AtomicBool::new(false));
+        let seen = ran.clone();
+        engine
+            .register_native_task("tools.hello", Vec::new(), move |_| {
+                seen.store(true, Ordering::SeqCst);
+                Ok(())
+            })
+            .expect("register native task");
+        engine.run_task("hello", &[]).expect("run without a script");
+        assert!(ran.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn run_task_allows_dependency_only_tasks() {
+        let script = write_script(
+            r#"
+            task("fmt", || {
+                actions(|| {});
+            });
+            task("ci", || {
+                deps(["fmt"]);
+            });
+        "#,
+        );
+        let mut engine = ScriptEngine::new();
+        engine
+            .run_script(script.path().to_str().unwrap())
+            .expect("load script");
+        engine
+            .run_task("ci", &[])
+            .expect("run dependency-only task");
+    }
+
+    #[test]
+    fn nested_triggers_keep_process_working_directory() {
+        let outer = tempfile::tempdir().expect("temp dir");
+        let inner = tempfile::tempdir().expect("temp dir");
+        let outer_dir = outer.path().canonicalize().unwrap();
+        let inner_dir = inner.path().canonicalize().unwrap();
+        let launch_dir = env::current_dir().expect("current dir");
+        let script = write_script(&format!(
+            r#"
+            fn check(expected) {{
+                if process_cwd() != `{launch}` {{
+                    throw "process cwd changed to " + process_cwd();
+                }}
+                if cwd() != expected {{
+                    throw "cwd() returned " + cwd();
+                }}
+            }}
+            task("plain", || {{
+                actions(|| {{ check(`{launch}`); }});
+            }});
+            task("inner", || {{
+                dir(`{inner}`);
+                actions(|| {{
+                    check(`{inner}`);
+                    trigger("plain");
+                    check(`{inner}`);
+                }});
+            }});
+            task("outer", || {{
+                dir(`{outer}`);
+                actions(|| {{
+                    check(`{outer}`);
+                    trigger("inner");
+                    check(`{outer}`);
+                }});
+            }});
+        "#,
+            launch = launch_dir.display(),
+            inner = inner_dir.display(),
+            outer = outer_dir.display(),
+        ));
+        let mut engine = ScriptEngine::new();
+        engine.engine.register_fn("process_cwd", || {
+            env::current_dir()
+                .expect("current dir")
+                .display()
+                .to_string()
+        });
+        engine
+            .run_script(script.path().to_str().unwrap())
+            .expect("load script");
+        engine
+            .run_task("outer", &[])
+            .expect("nested triggers keep process cwd");
+        assert_eq!(env::current_dir().expect("current dir"), launch_dir);
+    }
+
+    #[test]
+    fn run_and_exec_both_use_task_directory() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let task_dir = dir.path().canonicalize().unwrap();
+        let script = write_script(&format!(
+            r#"
+            task("where", || {{
+                dir(`{dir}`);
+                actions(|| {{
+                    let expected = `{dir}` + "\n";
+                    let ran = cmd(["pwd"]).build().run().stdout;
+                    let streamed = "";
+                    cmd(["pwd"]).build().run_stream(|line| streamed += line);
+                    let executed = exec(cmd(["pwd"]).build()).stdout;
+                    if ran != expected || streamed != expected || executed != expected {{
+                        throw `run: ${{ran}} run_stream: ${{streamed}} exec: ${{executed}}`;
+                    }}
+                }});
+            }});
+        "#,
+            dir = task_dir.display(),
+        ));
+        let mut engine = ScriptEngine::new();
+        engine
+            .run_script(script.path().to_str().unwrap())
+            .expect("load script");
+        engine
+            .run_task("where", &[])
+            .expect("run(), run_stream() and exec() honor dir()");
+    }
+
     #[test]
     fn discription_alias_sets_description() {
         let script = write_script(
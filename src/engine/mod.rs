@@ -1,6 +1,9 @@
 mod api;
 mod bindings;
 mod core;
+mod process;
 mod runtime;
+mod scheduler;
 
-pub use core::ScriptEngine;
+pub use core::{clean_cache, ScriptEngine, DEFAULT_MAX_TRIGGER_DEPTH};
+pub use scheduler::{RunSummary, SummaryEntry, TaskOutcome};
@@ -0,0 +1,267 @@
+use rhai::packages::Package;
+use rhai::{Array, Engine, EvalAltResult, FnPtr, ImmutableString, Map, AST, INT};
+use rhai_process::{CommandBuilder, Config, PipeBuilder, PipelineExecutor, ProcessPackage};
+use std::path::Path;
+
+/// Owns rhai-process' `cmd()` so rhask can wrap it while every builder shares one `Config`.
+pub(super) struct ProcessBridge {
+    engine: Engine,
+}
+
+impl ProcessBridge {
+    pub(super) fn new(config: Config) -> Self {
+        let mut engine = Engine::new_raw();
+        ProcessPackage::new(config).register_into_engine(&mut engine);
+        Self { engine }
+    }
+
+    /// Records `cmd(args)` after letting rhai-process check the program against its `Config`.
+    pub(super) fn command(&self, args: Array) -> Result<Command, Box<EvalAltResult>> {
+        let argv: Vec<String> = args.iter().map(ToString::to_string).collect();
+        let _: CommandBuilder = FnPtr::new("cmd")?.call(&self.engine, &AST::empty(), (args,))?;
+        Ok(Command {
+            stage: Stage {
+                argv,
+                env: Map::new(),
+            },
+        })
+    }
+
+    /// Records `.env(env)`; the values are checked by the builder's own `env()` right away.
+    pub(super) fn with_env(
+        &self,
+        mut command: Command,
+        env: Map,
+    ) -> Result<Command, Box<EvalAltResult>> {
+        self.builder(&command.stage.argv, [&env])?;
+        command.stage.env.extend(env);
+        Ok(command)
+    }
+
+    pub(super) fn with_env_var(
+        &self,
+        command: Command,
+        key: ImmutableString,
+        value: ImmutableString,
+    ) -> Result<Command, Box<EvalAltResult>> {
+        let mut env = Map::new();
+        env.insert(key.into(), value.into());
+        self.with_env(command, env)
+    }
+
+    /// Builds rhai-process' executor, with `task_env` applied under each stage's own `.env()`.
+    pub(super) fn pipeline(
+        &self,
+        executor: Executor,
+        task_env: &Map,
+    ) -> Result<PipelineExecutor, Box<EvalAltResult>> {
+        let mut stages = executor.stages.iter();
+        let first = stages.next().expect("a pipeline has at least one command");
+        let first = self.builder(&first.argv, [task_env, &first.env])?;
+        let pipeline: PipelineExecutor = match stages.next() {
+            None => FnPtr::new("build")?.call(&self.engine, &AST::empty(), (first,))?,
+            Some(second) => {
+                let second = self.builder(&second.argv, [task_env, &second.env])?;
+                let mut pipe: PipeBuilder =
+                    FnPtr::new("pipe")?.call(&self.engine, &AST::empty(), (first, second))?;
+                for stage in stages {
+                    pipe = pipe.pipe(self.builder(&stage.argv, [task_env, &stage.env])?)?;
+                }
+                pipe.build()
+            }
+        };
+        executor.configure(pipeline)
+    }
+
+    /// `cmd(argv)` followed by `.env()` for each non-empty map, later maps winning.
+    fn builder<'a>(
+        &self,
+        argv: &[String],
+        envs: impl IntoIterator<Item = &'a Map>,
+    ) -> Result<CommandBuilder, Box<EvalAltResult>> {
+        let args: Array = argv.iter().map(|word| word.as_str().into()).collect();
+        let mut builder = FnPtr::new("cmd")?.call(&self.engine, &AST::empty(), (args,))?;
+        for env in envs.into_iter().filter(|env| !env.is_empty()) {
+            builder =
+                FnPtr::new("env")?.call(&self.engine, &AST::empty(), (builder, env.clone()))?;
+        }
+        Ok(builder)
+    }
+}
+
+/// One stage of a pipeline: the argv given to `cmd()` and what `.env()`/`.env_var()` set.
+#[derive(Clone, Debug)]
+struct Stage {
+    argv: Vec<String>,
+    env: Map,
+}
+
+/// What scripts see as `CommandBuilder`.
+#[derive(Clone, Debug)]
+pub struct Command {
+    stage: Stage,
+}
+
+impl Command {
+    pub(super) fn pipe(self, next: Command) -> Pipe {
+        Pipe {
+            stages: vec![self.stage, next.stage],
+        }
+    }
+
+    pub(super) fn build(self) -> Executor {
+        Executor::new(vec![self.stage])
+    }
+}
+
+/// What scripts see as `PipeBuilder`.
+#[derive(Clone, Debug)]
+pub struct Pipe {
+    stages: Vec<Stage>,
+}
+
+impl Pipe {
+    pub(super) fn pipe(mut self, next: Command) -> Pipe {
+        self.stages.push(next.stage);
+        self
+    }
+
+    pub(super) fn build(self) -> Executor {
+        Executor::new(self.stages)
+    }
+}
+
+/// What scripts see as `PipelineExecutor`. rhai-process' executor is only built when the
+/// pipeline runs (see `ProcessBridge::pipeline`), so the settings are kept until then.
+#[derive(Clone, Debug)]
+pub struct Executor {
+    stages: Vec<Stage>,
+    cwd: Option<String>,
+    timeout: Option<INT>,
+    allow_exit_codes: Option<Array>,
+}
+
+impl Executor {
+    fn new(stages: Vec<Stage>) -> Self {
+        Self {
+            stages,
+            cwd: None,
+            timeout: None,
+            allow_exit_codes: None,
+        }
+    }
+
+    pub(super) fn cwd(self, path: String) -> Self {
+        Self {
+            cwd: Some(path),
+            ..self
+        }
+    }
+
+    pub(super) fn timeout(self, timeout: INT) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    pub(super) fn allow_exit_codes(self, codes: Array) -> Self {
+        Self {
+            allow_exit_codes: Some(codes),
+            ..self
+        }
+    }
+
+    /// Hands the recorded settings to rhai-process' executor, which validates them.
+    fn configure(
+        self,
+        mut pipeline: PipelineExecutor,
+    ) -> Result<PipelineExecutor, Box<EvalAltResult>> {
+        if let Some(path) = self.cwd {
+            pipeline = pipeline.cwd(path)?;
+        }
+        if let Some(timeout) = self.timeout {
+            pipeline = pipeline.timeout(timeout)?;
+        }
+        if let Some(codes) = self.allow_exit_codes {
+            pipeline = pipeline.allow_exit_codes(codes)?;
+        }
+        Ok(pipeline)
+    }
+
+    /// Shell-like rendering for error messages, e.g. `cargo test | tee log`.
+    pub(super) fn command_line(&self) -> String {
+        self.stages
+            .iter()
+            .map(|stage| {
+                stage
+                    .argv
+                    .iter()
+                    .map(|word| shell_word(word))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+/// Registers the wrappers under rhai-process' type names, so `type_of()` is unchanged.
+pub(super) fn register_types(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<Command>("CommandBuilder")
+        .register_type_with_name::<Pipe>("PipeBuilder")
+        .register_type_with_name::<Executor>("PipelineExecutor");
+}
+
+fn shell_word(word: &str) -> String {
+    let plain = !word.is_empty()
+        && !word.contains(|ch: char| ch.is_whitespace() || matches!(ch, '"' | '\'' | '\\'));
+    if plain {
+        word.to_string()
+    } else {
+        format!("{:?}", word)
+    }
+}
+
+/// Relative program paths such as `./hello.sh` are resolved against the task's `dir()`
+/// because the process working directory is never changed.
+pub(super) fn resolve_program(working_dir: &Path, program: &str) -> Option<String> {
+    let path = Path::new(program);
+    if path.is_absolute() || path.components().count() < 2 {
+        return None;
+    }
+    working_dir.join(path).to_str().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_line_renders_every_command_of_a_pipeline() {
+        let bridge = ProcessBridge::new(Config::default());
+        let first = bridge
+            .command(vec!["sh".into(), "-c".into(), "echo \"hi\"".into()])
+            .expect("cmd");
+        let second = bridge
+            .command(vec!["tr".into(), "a-z".into(), "A-Z".into()])
+            .expect("cmd");
+        let executor = first.pipe(second).build().timeout(5_000);
+        assert_eq!(
+            executor.command_line(),
+            r#"sh -c "echo \"hi\"" | tr a-z A-Z"#
+        );
+    }
+
+    #[test]
+    fn resolve_program_only_rewrites_relative_paths() {
+        let dir = Path::new("/work/scripts");
+        assert_eq!(
+            resolve_program(dir, "./hello.sh").as_deref(),
+            Some("/work/scripts/./hello.sh")
+        );
+        assert_eq!(resolve_program(dir, "cargo"), None);
+        assert_eq!(resolve_program(dir, "/usr/bin/env"), None);
+    }
+}
@@ -1,12 +1,16 @@
 use indexmap::IndexMap;
-use rhai::{
-    Array, Dynamic, EvalAltResult, FnPtr, ImmutableString, Map, NativeCallContext, Position,
-};
+use rhai::{Array, Dynamic, EvalAltResult, ImmutableString, Map, NativeCallContext, Position};
 use std::sync::{Arc, Mutex};
 
-use super::core::{actions_only_error, user_error, ActionScope, ExecutionState};
+use super::core::{
+    actions_only_error, call_native, run_arguments, user_error, ActionScope, ExecutionState,
+};
+use super::process::ProcessBridge;
 use crate::logger::{error, trace, warn};
-use crate::task::{prepare_arguments_from_parts, BuildStack, TaskLookup, TaskRegistry};
+use crate::task::{
+    prepare_arguments_from_parts, BuildStack, PendingRun, TaskAction, TaskLookup, TaskRegistry,
+};
+use rhai_process::Config;
 
 pub(super) type RegistryRef = Arc<Mutex<TaskRegistry>>;
 pub(super) type BuildStackRef = Arc<Mutex<BuildStack>>;
@@ -16,6 +20,7 @@ pub(super) struct RuntimeHandle {
     pub(super) registry: RegistryRef,
     pub(super) exec_state: Arc<Mutex<ExecutionState>>,
     pub(super) build_stack: BuildStackRef,
+    pub(super) process: Arc<ProcessBridge>,
 }
 
 impl RuntimeHandle {
@@ -23,11 +28,13 @@ impl RuntimeHandle {
         registry: RegistryRef,
         exec_state: Arc<Mutex<ExecutionState>>,
         build_stack: BuildStackRef,
+        process_config: Config,
     ) -> Self {
         Self {
             registry,
             exec_state,
             build_stack,
+            process: Arc::new(ProcessBridge::new(process_config)),
         }
     }
 }
@@ -35,8 +42,9 @@ impl RuntimeHandle {
 pub(super) fn trigger_impl(
     ctx: &NativeCallContext,
     name: &str,
-    positional: Vec<String>,
-    named: IndexMap<String, String>,
+    positional: Vec<Dynamic>,
+    named: IndexMap<String, Dynamic>,
+    passthrough: Vec<String>,
 ) -> Result<(), Box<EvalAltResult>> {
     let runtime = runtime_from_ctx(ctx)?;
     let registry = &runtime.registry;
@@ -54,13 +62,14 @@ pub(super) fn trigger_impl(
 
     match lookup {
         TaskLookup::Found { full_path } => {
-            let (func, args, task_dir) = {
+            let (func, args, settings) = {
                 let reg = registry.lock().unwrap();
                 let args = prepare_arguments_from_parts(&reg, &full_path, positional, named)?;
                 let task_meta = reg.task(&full_path);
                 let action = task_meta.and_then(|task| task.actions.clone());
-                let working_dir = task_meta.and_then(|task| task.working_dir.clone());
-                (action, args, working_dir)
+                let mut settings = reg.effective_settings(&full_path);
+                settings.passthrough = passthrough;
+                (action, args, settings)
             };
 
             {
@@ -74,14 +83,24 @@ pub(super) fn trigger_impl(
                 }
             }
 
+            let has_deps = run_dependencies_within_context(ctx, &runtime, &full_path)?;
+
             if let Some(func) = func {
+                let run_args = run_arguments(&args, &settings.passthrough);
+                let Some(pending) = plan_run(&runtime, &full_path, &run_args) else {
+                    return Ok(());
+                };
                 trace!(
                     "trigger_impl executing '{}' with {} argument(s)",
                     full_path,
                     args.len()
                 );
-                let _scope = ActionScope::start_nested(state.clone(), "trigger()", task_dir)?;
-                call_with_context(ctx, &func, args)?;
+                let scope =
+                    ActionScope::start_nested(state.clone(), "trigger()", &full_path, settings)?;
+                scope.run(|| call_with_context(ctx, &func, args))?;
+                pending.complete();
+                Ok(())
+            } else if has_deps {
                 Ok(())
             } else {
                 warn!(
@@ -116,31 +135,100 @@ pub(super) fn trigger_impl(
     }
 }
 
+fn run_dependencies_within_context(
+    ctx: &NativeCallContext,
+    runtime: &RuntimeHandle,
+    full_path: &str,
+) -> Result<bool, Box<EvalAltResult>> {
+    let order = {
+        let reg = runtime.registry.lock().unwrap();
+        reg.dependency_order(full_path)?
+    };
+    let has_deps = !order.is_empty();
+
+    for dep in order {
+        if !runtime.exec_state.lock().unwrap().mark_completed(&dep) {
+            trace!("trigger_impl: dependency '{}' already completed", dep);
+            continue;
+        }
+        let (func, args, settings) = {
+            let reg = runtime.registry.lock().unwrap();
+            let args = prepare_arguments_from_parts(&reg, &dep, Vec::new(), IndexMap::new())?;
+            let task_meta = reg.task(&dep);
+            let action = task_meta.and_then(|task| task.actions.clone());
+            (action, args, reg.effective_settings(&dep))
+        };
+        if let Some(func) = func {
+            let Some(pending) = plan_run(runtime, &dep, &args) else {
+                continue;
+            };
+            trace!(
+                "trigger_impl: running dependency '{}' of '{}'",
+                dep,
+                full_path
+            );
+            let scope =
+                ActionScope::start_nested(runtime.exec_state.clone(), "trigger()", &dep, settings)?;
+            scope.run(|| call_with_context(ctx, &func, args))?;
+            pending.complete();
+        }
+    }
+    Ok(has_deps)
+}
+
+fn plan_run(runtime: &RuntimeHandle, full_path: &str, args: &[Dynamic]) -> Option<PendingRun> {
+    let (force, cache_dir) = {
+        let state = runtime.exec_state.lock().unwrap();
+        (state.force(), state.cache_dir())
+    };
+    runtime
+        .registry
+        .lock()
+        .unwrap()
+        .plan_run(full_path, args, force, cache_dir.as_deref())
+}
+
 fn call_with_context(
     ctx: &NativeCallContext,
-    func: &FnPtr,
+    action: &TaskAction,
     args: Vec<Dynamic>,
 ) -> Result<(), Box<EvalAltResult>> {
-    let _ = func.call_within_context::<Dynamic>(ctx, args)?;
-    Ok(())
+    match action {
+        TaskAction::Script(func) => {
+            let _ = func.call_within_context::<Dynamic>(ctx, args)?;
+            Ok(())
+        }
+        TaskAction::Native(func) => call_native(func, &args),
+    }
 }
 
-pub(super) fn array_to_positional(array: Array) -> Result<Vec<String>, Box<EvalAltResult>> {
-    array
-        .into_iter()
-        .map(|value| Ok(value.to_string()))
-        .collect()
+/// Splits a `trigger()` positional array at a `"--"` element, mirroring the CLI.
+pub(super) fn array_to_positional(
+    mut array: Array,
+) -> Result<(Vec<Dynamic>, Vec<String>), Box<EvalAltResult>> {
+    let separator = array.iter().position(|value| {
+        value
+            .read_lock::<ImmutableString>()
+            .is_some_and(|text| text.as_str() == "--")
+    });
+    let passthrough = match separator {
+        Some(index) => {
+            let rest = array.split_off(index + 1);
+            array.pop();
+            rest.into_iter().map(|value| value.to_string()).collect()
+        }
+        None => Vec::new(),
+    };
+    Ok((array, passthrough))
 }
 
-pub(super) fn map_to_named(map: Map) -> Result<IndexMap<String, String>, Box<EvalAltResult>> {
+pub(super) fn map_to_named(map: Map) -> Result<IndexMap<String, Dynamic>, Box<EvalAltResult>> {
     let mut named = IndexMap::new();
     for (key, value) in map.into_iter() {
         let value = if value.is_unit() {
-            String::new()
-        } else if let Some(s) = value.clone().try_cast::<ImmutableString>() {
-            s.into()
+            Dynamic::from(String::new())
         } else {
-            value.to_string()
+            value
         };
         named.insert(key.into(), value);
     }
@@ -211,11 +299,13 @@ impl ScopeGuard {
             match kind {
                 ScopeKind::Task => {
                     let registry = runtime.registry.lock().unwrap();
-                    stack.begin_task(&registry, identifier)?
+                    stack.begin_task(&registry, identifier)?;
+                    stack.record_declaration(ctx.call_source(), ctx.call_position());
                 }
                 ScopeKind::Group => {
                     let registry = runtime.registry.lock().unwrap();
-                    stack.begin_group(&registry, identifier)?
+                    stack.begin_group(&registry, identifier)?;
+                    stack.record_declaration(ctx.call_source(), ctx.call_position());
                 }
             }
         }
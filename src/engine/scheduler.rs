@@ -0,0 +1,210 @@
+use indexmap::IndexMap;
+use rhai::{Dynamic, EvalAltResult, AST};
+use std::collections::HashSet;
+use std::sync::atomic::Ordering;
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::core::{run_arguments, ActionScope, ScriptEngine};
+use crate::logger::{debug, trace};
+use crate::task::{EffectiveSettings, TaskAction};
+
+const WORKER_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+pub(super) struct ScheduledTask {
+    pub(super) full_path: String,
+    pub(super) deps: Vec<String>,
+    pub(super) args: Vec<Dynamic>,
+    pub(super) actions: Option<TaskAction>,
+    pub(super) settings: EffectiveSettings,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskOutcome {
+    Succeeded,
+    Failed,
+    Cancelled,
+    NotStarted,
+}
+
+#[derive(Debug, Clone)]
+pub struct SummaryEntry {
+    pub full_path: String,
+    pub outcome: TaskOutcome,
+    pub duration: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RunSummary {
+    pub entries: Vec<SummaryEntry>,
+    pub elapsed: Duration,
+}
+
+impl RunSummary {
+    pub fn count(&self, outcome: TaskOutcome) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.outcome == outcome)
+            .count()
+    }
+}
+
+struct Queue {
+    pending: Vec<ScheduledTask>,
+    finished: HashSet<String>,
+    running: usize,
+    failure: Option<Box<EvalAltResult>>,
+    entries: IndexMap<String, SummaryEntry>,
+}
+
+impl Queue {
+    fn next_ready(&mut self) -> Option<ScheduledTask> {
+        let index = self
+            .pending
+            .iter()
+            .position(|task| task.deps.iter().all(|dep| self.finished.contains(dep)))?;
+        self.running += 1;
+        Some(self.pending.remove(index))
+    }
+}
+
+/// Runs `tasks` (already in dependency order) on up to `jobs` workers.
+/// The first failure stops running scripts and prevents new tasks from starting; processes
+/// already spawned by `exec()` are not killed, and the run waits for them to exit.
+pub(super) fn run_parallel(
+    engine: &ScriptEngine,
+    ast: &AST,
+    tasks: Vec<ScheduledTask>,
+    jobs: usize,
+) -> (RunSummary, Result<(), Box<EvalAltResult>>) {
+    let started = Instant::now();
+    engine.cancel.store(false, Ordering::SeqCst);
+
+    let entries = tasks
+        .iter()
+        .map(|task| {
+            let entry = SummaryEntry {
+                full_path: task.full_path.clone(),
+                outcome: TaskOutcome::NotStarted,
+                duration: None,
+            };
+            (task.full_path.clone(), entry)
+        })
+        .collect();
+    let workers = jobs.min(tasks.len()).max(1);
+    let queue = Mutex::new(Queue {
+        pending: tasks,
+        finished: HashSet::new(),
+        running: 0,
+        failure: None,
+        entries,
+    });
+    let ready = Condvar::new();
+
+    debug!("run_parallel: starting {} worker(s)", workers);
+    thread::scope(|scope| {
+        for index in 0..workers {
+            thread::Builder::new()
+                .name(format!("rhask-worker-{}", index))
+                .stack_size(WORKER_STACK_SIZE)
+                .spawn_scoped(scope, || worker(engine, ast, &queue, &ready))
+                .expect("spawn rhask worker thread");
+        }
+    });
+    engine.cancel.store(false, Ordering::SeqCst);
+
+    let queue = queue.into_inner().unwrap();
+    let summary = RunSummary {
+        entries: queue.entries.into_values().collect(),
+        elapsed: started.elapsed(),
+    };
+    let result = match queue.failure {
+        Some(err) => Err(err),
+        None => Ok(()),
+    };
+    (summary, result)
+}
+
+fn worker(engine: &ScriptEngine, ast: &AST, queue: &Mutex<Queue>, ready: &Condvar) {
+    loop {
+        let task = {
+            let mut guard = queue.lock().unwrap();
+            loop {
+                if guard.failure.is_some() {
+                    return;
+                }
+                if let Some(task) = guard.next_ready() {
+                    break task;
+                }
+                if guard.pending.is_empty() || guard.running == 0 {
+                    return;
+                }
+                guard = ready.wait(guard).unwrap();
+            }
+        };
+
+        trace!("worker: running '{}'", task.full_path);
+        let started = Instant::now();
+        let result = run_scheduled(engine, ast, &task);
+        let elapsed = started.elapsed();
+
+        let mut guard = queue.lock().unwrap();
+        guard.running -= 1;
+        let outcome = match result {
+            Ok(()) => {
+                guard.finished.insert(task.full_path.clone());
+                TaskOutcome::Succeeded
+            }
+            Err(err) if engine.cancel.load(Ordering::SeqCst) && is_termination(&err) => {
+                TaskOutcome::Cancelled
+            }
+            Err(err) => {
+                debug!(
+                    "worker: '{}' failed, cancelling remaining tasks",
+                    task.full_path
+                );
+                engine.cancel.store(true, Ordering::SeqCst);
+                if guard.failure.is_none() {
+                    guard.failure = Some(err);
+                }
+                TaskOutcome::Failed
+            }
+        };
+        if let Some(entry) = guard.entries.get_mut(&task.full_path) {
+            entry.outcome = outcome;
+            entry.duration = Some(elapsed);
+        }
+        ready.notify_all();
+    }
+}
+
+fn run_scheduled(
+    engine: &ScriptEngine,
+    ast: &AST,
+    task: &ScheduledTask,
+) -> Result<(), Box<EvalAltResult>> {
+    engine
+        .exec_state
+        .lock()
+        .unwrap()
+        .mark_completed(&task.full_path);
+    if let Some(func) = &task.actions {
+        let run_args = run_arguments(&task.args, &task.settings.passthrough);
+        let Some(pending) = engine.plan_run(&task.full_path, &run_args) else {
+            return Ok(());
+        };
+        let scope = ActionScope::start(
+            engine.exec_state.clone(),
+            &task.full_path,
+            task.settings.clone(),
+        )?;
+        scope.run(|| engine.invoke_action(ast, func, task.args.clone()))?;
+        pending.complete();
+    }
+    Ok(())
+}
+
+fn is_termination(err: &EvalAltResult) -> bool {
+    matches!(err.unwrap_inner(), EvalAltResult::ErrorTerminated(..))
+}
@@ -0,0 +1,210 @@
+use rhai::{EvalAltResult, Position};
+use std::error::Error;
+use std::fmt;
+
+/// Exit status for failures without a more specific code.
+pub const EXIT_FAILURE: i32 = 1;
+/// Exit status when the arguments given to a task are invalid.
+pub const EXIT_INVALID_ARGUMENTS: i32 = 64;
+/// Exit status when the rhaskfile (or a module it imports) does not compile.
+pub const EXIT_SCRIPT_ERROR: i32 = 65;
+/// Exit status when no task matches the requested name.
+pub const EXIT_TASK_NOT_FOUND: i32 = 66;
+/// Exit status when the requested name matches several tasks.
+pub const EXIT_AMBIGUOUS_TASK: i32 = 67;
+
+/// Error returned by rhask's library API.
+///
+/// Inside Rhai it travels as `EvalAltResult::ErrorSystem` (see the `From` impls), so it
+/// survives the script layer and comes back out unchanged.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RhaskError {
+    /// No task matches `name`.
+    TaskNotFound { name: String },
+    /// `name` matches several tasks; `candidates` are their full paths.
+    Ambiguous {
+        name: String,
+        candidates: Vec<String>,
+    },
+    /// The arguments given to `task` are invalid.
+    ArgumentError {
+        task: String,
+        source: Box<EvalAltResult>,
+    },
+    /// The rhaskfile or a module it imports does not compile.
+    ScriptError {
+        position: Position,
+        source: Box<EvalAltResult>,
+    },
+    /// `command` exited with `status`, failing the tasks in `chain` (outermost first).
+    ExecFailed {
+        status: i32,
+        command: String,
+        chain: Vec<String>,
+        source: Box<EvalAltResult>,
+    },
+    /// Any other error raised while loading the script or running actions.
+    Runtime(Box<EvalAltResult>),
+}
+
+impl RhaskError {
+    /// Process exit status for this error: the status of the failed command, or one of the
+    /// `EXIT_*` codes. A command status outside 1..=255 (rhai-process reports a command
+    /// killed by a signal or its timeout as -1) becomes `EXIT_FAILURE`.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RhaskError::TaskNotFound { .. } => EXIT_TASK_NOT_FOUND,
+            RhaskError::Ambiguous { .. } => EXIT_AMBIGUOUS_TASK,
+            RhaskError::ArgumentError { .. } => EXIT_INVALID_ARGUMENTS,
+            RhaskError::ScriptError { .. } => EXIT_SCRIPT_ERROR,
+            RhaskError::ExecFailed { status, .. } if (1..=255).contains(status) => *status,
+            RhaskError::ExecFailed { .. } => EXIT_FAILURE,
+            RhaskError::Runtime(_) => EXIT_FAILURE,
+        }
+    }
+}
+
+impl fmt::Display for RhaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RhaskError::TaskNotFound { name } => write!(f, "Task '{}' does not exist.", name),
+            RhaskError::Ambiguous { name, candidates } => {
+                writeln!(f, "Task '{}' matches multiple candidates:", name)?;
+                for candidate in candidates {
+                    writeln!(f, "  - {}", candidate)?;
+                }
+                f.write_str("Please use the fully-qualified name (e.g. group.task).")
+            }
+            RhaskError::ArgumentError { source, .. } => match source.unwrap_inner() {
+                EvalAltResult::ErrorRuntime(value, _) => write!(f, "{}", value),
+                inner => write!(f, "{}", inner),
+            },
+            RhaskError::ScriptError { source, .. } | RhaskError::Runtime(source) => {
+                write!(f, "{}", source)
+            }
+            RhaskError::ExecFailed { chain, source, .. } => {
+                write!(f, "{}: ", chain.join(" -> "))?;
+                match source.unwrap_inner() {
+                    EvalAltResult::ErrorRuntime(value, position) if position.is_none() => {
+                        write!(f, "{}", value)
+                    }
+                    EvalAltResult::ErrorRuntime(value, position) => {
+                        write!(f, "{} ({})", value, position)
+                    }
+                    inner => write!(f, "{}", inner),
+                }
+            }
+        }
+    }
+}
+
+impl Error for RhaskError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RhaskError::TaskNotFound { .. } | RhaskError::Ambiguous { .. } => None,
+            RhaskError::ArgumentError { source, .. }
+            | RhaskError::ScriptError { source, .. }
+            | RhaskError::ExecFailed { source, .. }
+            | RhaskError::Runtime(source) => Some(source.as_ref()),
+        }
+    }
+}
+
+impl From<Box<EvalAltResult>> for RhaskError {
+    fn from(err: Box<EvalAltResult>) -> Self {
+        if let EvalAltResult::ErrorParsing(_, position) = err.unwrap_inner() {
+            return RhaskError::ScriptError {
+                position: *position,
+                source: err,
+            };
+        }
+        match *err {
+            EvalAltResult::ErrorSystem(message, source) => match source.downcast::<RhaskError>() {
+                Ok(rhask_error) => *rhask_error,
+                Err(source) => {
+                    RhaskError::Runtime(Box::new(EvalAltResult::ErrorSystem(message, source)))
+                }
+            },
+            other => RhaskError::Runtime(Box::new(other)),
+        }
+    }
+}
+
+/// Converts at the script boundary. [`RhaskError::Runtime`] gives back the original Rhai
+/// error; everything else is wrapped so the typed error can be recovered later.
+impl From<RhaskError> for Box<EvalAltResult> {
+    fn from(err: RhaskError) -> Self {
+        match err {
+            RhaskError::Runtime(source) => source,
+            other => Box::new(EvalAltResult::ErrorSystem(String::new(), Box::new(other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rhai::Engine;
+
+    #[test]
+    fn exit_code_distinguishes_failure_kinds() {
+        let parse_error: Box<EvalAltResult> =
+            Engine::new().compile("let x = ;").unwrap_err().into();
+        let script_error = RhaskError::from(parse_error);
+        assert!(matches!(
+            script_error,
+            RhaskError::ScriptError { position, .. } if position.line() == Some(1)
+        ));
+        assert_eq!(script_error.exit_code(), EXIT_SCRIPT_ERROR);
+
+        let runtime = EvalAltResult::ErrorRuntime("boom".into(), Position::NONE);
+        let runtime = RhaskError::from(Box::new(runtime));
+        assert_eq!(runtime.exit_code(), EXIT_FAILURE);
+        assert!(runtime.source().is_some());
+
+        let exec_failed = |status| RhaskError::ExecFailed {
+            status,
+            command: "sleep 60".to_string(),
+            chain: vec!["wait".to_string()],
+            source: Box::new(EvalAltResult::ErrorRuntime("failed".into(), Position::NONE)),
+        };
+        assert_eq!(exec_failed(101).exit_code(), 101);
+        assert_eq!(exec_failed(255).exit_code(), 255);
+        assert_eq!(exec_failed(-1).exit_code(), EXIT_FAILURE);
+        assert_eq!(exec_failed(0).exit_code(), EXIT_FAILURE);
+        assert_eq!(exec_failed(256).exit_code(), EXIT_FAILURE);
+    }
+
+    #[test]
+    fn typed_errors_survive_the_script_boundary() {
+        let failed = RhaskError::ExecFailed {
+            status: 101,
+            command: "cargo test".to_string(),
+            chain: vec!["ci".to_string(), "unit.test".to_string()],
+            source: Box::new(EvalAltResult::ErrorRuntime(
+                "`cargo test` exited with status 101".into(),
+                Position::new(4, 13),
+            )),
+        };
+        let rhai_error: Box<EvalAltResult> = failed.into();
+        let back = RhaskError::from(rhai_error);
+        assert_eq!(back.exit_code(), 101);
+        assert_eq!(
+            back.to_string(),
+            "ci -> unit.test: `cargo test` exited with status 101 (line 4, position 13)"
+        );
+
+        let ambiguous: Box<EvalAltResult> = RhaskError::Ambiguous {
+            name: "deploy".to_string(),
+            candidates: vec!["build.deploy".to_string(), "ops.deploy".to_string()],
+        }
+        .into();
+        match RhaskError::from(ambiguous) {
+            RhaskError::Ambiguous { candidates, .. } => {
+                assert_eq!(candidates, ["build.deploy", "ops.deploy"])
+            }
+            other => panic!("expected ambiguity, got {:?}", other),
+        }
+    }
+}
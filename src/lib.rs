@@ -3,11 +3,13 @@
 pub mod cli;
 pub mod completions;
 pub mod engine;
+pub mod error;
 pub mod logger;
 pub mod printer;
 pub mod task;
 
 pub use completions::print as print_shell_completions;
+pub use error::RhaskError;
 
 use clap::Parser;
 use cli::Cli;
@@ -15,12 +17,12 @@ use logger::*;
 use rhai::{EvalAltResult, Position};
 use std::io::{self, Write};
 
-pub fn run() -> Result<(), Box<EvalAltResult>> {
+pub fn run() -> Result<(), RhaskError> {
     let cli = Cli::parse();
     run_with_cli(cli)
 }
 
-pub fn run_with_cli(cli: Cli) -> Result<(), Box<EvalAltResult>> {
+pub fn run_with_cli(cli: Cli) -> Result<(), RhaskError> {
     logger::init();
     info!("start");
     debug!("cli args: {:?}", cli);
@@ -36,8 +38,17 @@ pub fn run_with_cli(cli: Cli) -> Result<(), Box<EvalAltResult>> {
             info!("{} end", env!("CARGO_PKG_NAME"));
             Ok(())
         }
+        Some(cli::Commands::Cache(opts)) => {
+            match opts.action {
+                cli::CacheAction::Clean => engine::clean_cache(&script_path)?,
+            }
+            info!("{} end", env!("CARGO_PKG_NAME"));
+            Ok(())
+        }
         other => {
             let mut script_engine = engine::ScriptEngine::new();
+            script_engine.set_jobs(cli.jobs);
+            script_engine.set_max_trigger_depth(cli.max_trigger_depth);
             script_engine.run_script(&script_path)?;
             dispatcher(other, script_engine)?;
             info!("{} end", env!("CARGO_PKG_NAME"));
@@ -48,21 +59,59 @@ pub fn run_with_cli(cli: Cli) -> Result<(), Box<EvalAltResult>> {
 
 fn dispatcher(
     cmd: Option<cli::Commands>,
-    engine: engine::ScriptEngine,
-) -> Result<(), Box<EvalAltResult>> {
+    mut engine: engine::ScriptEngine,
+) -> Result<(), RhaskError> {
     debug!("dispatching command: {:?}", cmd);
     match cmd {
         Some(cli::Commands::List(opts)) => {
-            info!("Listing tasks: group={:?}, flat={}", opts.group, opts.flat);
-            engine.list_tasks(opts.group.as_deref(), opts.flat);
+            info!(
+                "Listing tasks: group={:?}, flat={}, settings={}",
+                opts.group, opts.flat, opts.settings
+            );
+            let export_format = match opts.format {
+                cli::ListFormat::Text => None,
+                cli::ListFormat::Json => Some(task::ExportFormat::Json),
+                cli::ListFormat::Yaml => Some(task::ExportFormat::Yaml),
+                cli::ListFormat::Tsv => Some(task::ExportFormat::Tsv),
+            };
+            if let Some(format) = export_format {
+                engine.export_tasks(opts.group.as_deref(), format);
+            } else if opts.settings {
+                engine.list_task_settings(opts.group.as_deref(), opts.flat);
+            } else {
+                engine.list_tasks(opts.group.as_deref(), opts.flat);
+            }
             Ok(())
         }
-        Some(cli::Commands::Run(opts)) => run_with_logging(engine, &opts.task, &opts.args),
+        Some(cli::Commands::Status(opts)) => {
+            info!("Showing task status: group={:?}", opts.group);
+            engine.show_status(opts.group.as_deref());
+            Ok(())
+        }
+        Some(cli::Commands::Run(opts)) => {
+            engine.set_force(opts.force);
+            let mut args = opts.args;
+            if !opts.passthrough.is_empty() {
+                args.push("--".to_string());
+                args.extend(opts.passthrough);
+            }
+            run_with_logging(engine, &opts.task, &args)
+        }
+        Some(cli::Commands::Show(opts)) => {
+            info!("Showing task details: {}", opts.task);
+            engine.show_task(&opts.task)
+        }
         Some(cli::Commands::CompleteTasks(opts)) => {
-            print_task_candidates(&engine, opts.prefix.as_deref().unwrap_or_default());
+            if let Some(task) = opts.args_of.as_deref() {
+                print_argument_candidates(&engine, task);
+            } else {
+                print_task_candidates(&engine, opts.prefix.as_deref().unwrap_or_default());
+            }
             Ok(())
         }
-        Some(cli::Commands::Completions(_)) => unreachable!("handled earlier in run_with_cli"),
+        Some(cli::Commands::Completions(_)) | Some(cli::Commands::Cache(_)) => {
+            unreachable!("handled earlier in run_with_cli")
+        }
         Some(cli::Commands::Direct(raw)) => {
             let (task, args) = raw.split_first().ok_or_else(|| {
                 warn!("Direct command invoked without a task name");
@@ -86,7 +135,7 @@ fn run_with_logging(
     engine: engine::ScriptEngine,
     task: &str,
     args: &[String],
-) -> Result<(), Box<EvalAltResult>> {
+) -> Result<(), RhaskError> {
     info!("Executing task '{}'", task);
     if !args.is_empty() {
         debug!("Task '{}' arguments: {:?}", task, args);
@@ -107,15 +156,11 @@ fn missing_task_name_error() -> Box<EvalAltResult> {
 }
 
 fn print_task_candidates(engine: &engine::ScriptEngine, prefix: &str) {
-    let mut entries: Vec<String> = {
-        let registry = engine.registry.lock().unwrap();
-        let mut names: Vec<String> = registry
-            .tasks_iter()
-            .map(|(name, _)| name.clone())
-            .collect();
-        names.extend(registry.groups_iter().map(|(name, _)| name.clone()));
-        names
-    };
+    let mut entries: Vec<String> = engine
+        .tasks()
+        .into_iter()
+        .map(|info| info.full_path)
+        .collect();
 
     entries.sort();
     entries.dedup();
@@ -127,6 +172,36 @@ fn print_task_candidates(engine: &engine::ScriptEngine, prefix: &str) {
     let _ = stdout.flush();
 }
 
+/// Prints `--name<TAB>help` for each parameter of `task`, plus `--name=choice` per choice
+/// and `--no-name` for boolean switches.
+fn print_argument_candidates(engine: &engine::ScriptEngine, task: &str) {
+    let task::TaskLookup::Found { full_path } = engine.resolve(task) else {
+        return;
+    };
+    let Some(task) = engine.task_info(&full_path) else {
+        return;
+    };
+
+    let mut stdout = io::BufWriter::new(io::stdout());
+    for param in &task.params {
+        match &param.help {
+            Some(help) => {
+                let _ = writeln!(stdout, "--{}\t{}", param.name, help);
+            }
+            None => {
+                let _ = writeln!(stdout, "--{}", param.name);
+            }
+        }
+        for choice in &param.choices {
+            let _ = writeln!(stdout, "--{}={}", param.name, choice);
+        }
+        if param.ty == task::ParamType::Bool {
+            let _ = writeln!(stdout, "--no-{}", param.name);
+        }
+    }
+    let _ = stdout.flush();
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -158,6 +233,8 @@ mod tests {
         let opts = cli::ListOptions {
             group: Some("nonexistent".to_string()),
             flat: true,
+            settings: false,
+            format: cli::ListFormat::Text,
         };
         let result = dispatcher(Some(cli::Commands::List(opts)), engine);
         assert!(result.is_ok());
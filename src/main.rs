@@ -3,6 +3,6 @@ use rhask::run;
 fn main() {
     if let Err(err) = run() {
         eprintln!("error: {}", err);
-        std::process::exit(1);
+        std::process::exit(err.exit_code());
     }
 }
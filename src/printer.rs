@@ -2,7 +2,8 @@ use std::collections::HashMap;
 use std::io::{self, IsTerminal, Write};
 use std::sync::OnceLock;
 
-use crate::task::{ListItemKind, ListMessageLevel, ListOutput, ListRenderMode};
+use crate::engine::{RunSummary, TaskOutcome};
+use crate::task::{ListItem, ListItemKind, ListMessageLevel, ListOutput, ListRenderMode};
 
 const RESET: &str = "\x1b[0m";
 const FG_CYAN: &str = "\x1b[36m";
@@ -75,6 +76,7 @@ fn print_tree(output: &ListOutput, use_color: bool) {
         } else {
             info(base);
         }
+        print_settings(item, &format!("{}    ", indent), use_color);
     }
 }
 
@@ -111,6 +113,45 @@ fn print_flat(output: &ListOutput, use_color: bool) {
         } else {
             info(&padded_name);
         }
+        print_settings(item, "    ", use_color);
+    }
+}
+
+fn print_settings(item: &ListItem, indent: &str, use_color: bool) {
+    for setting in &item.settings {
+        if use_color {
+            info(format!("{indent}{FG_BRIGHT_BLACK}{setting}{RESET}"));
+        } else {
+            info(format!("{indent}{setting}"));
+        }
+    }
+}
+
+pub fn print_run_summary(summary: &RunSummary) {
+    warn(format!(
+        "Summary: {} succeeded, {} failed, {} cancelled, {} not started ({:.2}s)",
+        summary.count(TaskOutcome::Succeeded),
+        summary.count(TaskOutcome::Failed),
+        summary.count(TaskOutcome::Cancelled),
+        summary.count(TaskOutcome::NotStarted),
+        summary.elapsed.as_secs_f64()
+    ));
+    for entry in &summary.entries {
+        let label = match entry.outcome {
+            TaskOutcome::Succeeded => "ok",
+            TaskOutcome::Failed => "failed",
+            TaskOutcome::Cancelled => "cancelled",
+            TaskOutcome::NotStarted => "not started",
+        };
+        match entry.duration {
+            Some(duration) => warn(format!(
+                "  {:<11} {} ({:.2}s)",
+                label,
+                entry.full_path,
+                duration.as_secs_f64()
+            )),
+            None => warn(format!("  {:<11} {}", label, entry.full_path)),
+        }
     }
 }
 
@@ -154,7 +195,7 @@ fn colors_enabled() -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::task::{ListItem, ListMessage, ListMessageLevel, ListOutput};
+    use crate::task::{ListMessage, ListMessageLevel, ListOutput};
 
     fn sample_output() -> ListOutput {
         let mut output = ListOutput::default();
@@ -176,6 +217,7 @@ mod tests {
             name: "build".into(),
             full_name: "build".into(),
             description: Some("build tasks".into()),
+            settings: Vec::new(),
         });
         output.items.push(ListItem {
             kind: ListItemKind::Task,
@@ -183,6 +225,7 @@ mod tests {
             name: "debug".into(),
             full_name: "build.debug".into(),
             description: Some("debug build".into()),
+            settings: vec!["dir: scripts".into()],
         });
         output
     }
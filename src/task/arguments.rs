@@ -1,17 +1,22 @@
 use indexmap::IndexMap;
-use rhai::{Dynamic, EvalAltResult, ImmutableString};
+use rhai::{Array, Dynamic, EvalAltResult, ImmutableString, FLOAT, INT};
 
 use crate::logger::{debug, trace};
 
-use super::model::context_error;
+use super::model::{context_error, ParamType, ParameterSpec};
 use super::registry::TaskRegistry;
 
 #[cfg(test)]
 use super::stack::BuildStack;
 
+/// Splits raw CLI words into positional values and named options. `params` tells switches
+/// apart from value-taking options: a boolean parameter accepts `--flag`, `--no-flag`, `-f`
+/// (and clustered `-fv`) without consuming the next word. Repeating an array parameter
+/// accumulates its values; repeating any other option is an error.
 #[allow(clippy::type_complexity)]
 pub fn parse_cli_arguments(
     raw_args: &[String],
+    params: &[ParameterSpec],
 ) -> Result<(Vec<String>, IndexMap<String, String>), Box<EvalAltResult>> {
     trace!("parse_cli_arguments: raw_args={:?}", raw_args);
     let mut positional = Vec::new();
@@ -25,6 +30,8 @@ pub fn parse_cli_arguments(
             }
             let (key, value) = if let Some((key, value)) = rest.split_once('=') {
                 (key.to_string(), value.to_string())
+            } else if let Some(switch) = long_switch(params, rest) {
+                switch
             } else if i + 1 < raw_args.len()
                 && !raw_args[i + 1].starts_with("--")
                 && !raw_args[i + 1].contains('=')
@@ -40,12 +47,34 @@ pub fn parse_cli_arguments(
             if key.is_empty() {
                 return Err(context_error("Argument name cannot be empty."));
             }
-            named.insert(key, value);
+            insert_named(&mut named, params, key, value)?;
+        } else if let Some(switches) = short_switches(params, arg) {
+            for name in switches {
+                insert_named(&mut named, params, name, "true".to_string())?;
+            }
+        } else if let Some(short) = short_flag(arg) {
+            let value = if let Some(value) = arg[2..].strip_prefix('=') {
+                value.to_string()
+            } else if i + 1 < raw_args.len() && !raw_args[i + 1].starts_with("--") {
+                i += 1;
+                raw_args[i].clone()
+            } else {
+                return Err(context_error(format!(
+                    "Option '-{}' is missing a value.",
+                    short
+                )));
+            };
+            let key = params
+                .iter()
+                .find(|param| param.short == Some(short))
+                .map(|param| param.name.clone())
+                .unwrap_or_else(|| format!("-{}", short));
+            insert_named(&mut named, params, key, value)?;
         } else if let Some((key, value)) = arg.split_once('=') {
             if key.is_empty() {
                 return Err(context_error("Argument name cannot be empty."));
             }
-            named.insert(key.to_string(), value.to_string());
+            insert_named(&mut named, params, key.to_string(), value.to_string())?;
         } else {
             positional.push(arg.clone());
         }
@@ -59,6 +88,83 @@ pub fn parse_cli_arguments(
     Ok((positional, named))
 }
 
+/// Records one named value. Array parameters join repeated values with `,`, which
+/// `parse_argument` splits again, so `--feature a --feature b,c` yields `["a", "b", "c"]`.
+fn insert_named(
+    named: &mut IndexMap<String, String>,
+    params: &[ParameterSpec],
+    key: String,
+    value: String,
+) -> Result<(), Box<EvalAltResult>> {
+    let Some(previous) = named.get_mut(&key) else {
+        named.insert(key, value);
+        return Ok(());
+    };
+    let is_array = params
+        .iter()
+        .any(|param| param.name == key && matches!(param.ty, ParamType::Array(_)));
+    if !is_array {
+        return Err(context_error(format!(
+            "Argument '{}' was given more than once.",
+            key
+        )));
+    }
+    previous.push(',');
+    previous.push_str(&value);
+    Ok(())
+}
+
+/// `--flag` / `--no-flag` for a boolean parameter, as `(name, "true" | "false")`.
+fn long_switch(params: &[ParameterSpec], option: &str) -> Option<(String, String)> {
+    let is_switch = |name: &str| {
+        params
+            .iter()
+            .any(|param| param.name == name && param.ty == ParamType::Bool)
+    };
+    if is_switch(option) {
+        return Some((option.to_string(), "true".to_string()));
+    }
+    let negated = option.strip_prefix("no-")?;
+    is_switch(negated).then(|| (negated.to_string(), "false".to_string()))
+}
+
+/// Names of the boolean parameters set by `-v` or a cluster such as `-vq`, if every letter
+/// is the short flag of a boolean parameter.
+fn short_switches(params: &[ParameterSpec], arg: &str) -> Option<Vec<String>> {
+    let letters = arg.strip_prefix('-')?;
+    if letters.is_empty() {
+        return None;
+    }
+    letters
+        .chars()
+        .map(|letter| {
+            params
+                .iter()
+                .find(|param| param.short == Some(letter) && param.ty == ParamType::Bool)
+                .map(|param| param.name.clone())
+        })
+        .collect()
+}
+
+/// `-t` or `-t=value`; anything else starting with `-` (such as `-5`) stays positional.
+fn short_flag(arg: &str) -> Option<char> {
+    let mut chars = arg.strip_prefix('-')?.chars();
+    let flag = chars.next().filter(char::is_ascii_alphabetic)?;
+    match chars.next() {
+        None | Some('=') => Some(flag),
+        Some(_) => None,
+    }
+}
+
+/// Splits off everything after the first `--`; those words reach the action untouched
+/// through `passthrough()`.
+pub fn split_passthrough(raw_args: &[String]) -> (&[String], Vec<String>) {
+    match raw_args.iter().position(|arg| arg == "--") {
+        Some(index) => (&raw_args[..index], raw_args[index + 1..].to_vec()),
+        None => (raw_args, Vec::new()),
+    }
+}
+
 pub fn prepare_arguments_from_cli(
     registry: &TaskRegistry,
     task_name: &str,
@@ -69,15 +175,27 @@ pub fn prepare_arguments_from_cli(
         task_name,
         raw_args
     );
-    let (positional, named) = parse_cli_arguments(raw_args)?;
+    let params = registry
+        .task(task_name)
+        .map(|task| task.params.as_slice())
+        .unwrap_or_default();
+    let (positional, named) = parse_cli_arguments(raw_args, params)?;
+    let positional = positional.into_iter().map(Dynamic::from).collect();
+    let named = named
+        .into_iter()
+        .map(|(key, value)| (key, Dynamic::from(value)))
+        .collect();
     prepare_arguments_from_parts(registry, task_name, positional, named)
 }
 
+/// Matches `positional`/`named` values to the task's parameters and converts each one to
+/// the parameter's type. Strings (as they arrive from the CLI) are parsed; other values
+/// (from `trigger()`) must already have a compatible type.
 pub fn prepare_arguments_from_parts(
     registry: &TaskRegistry,
     task_name: &str,
-    positional: Vec<String>,
-    named: IndexMap<String, String>,
+    positional: Vec<Dynamic>,
+    named: IndexMap<String, Dynamic>,
 ) -> Result<Vec<Dynamic>, Box<EvalAltResult>> {
     debug!(
         "prepare_arguments_from_parts: task='{}', positional={:?}, named={:?}",
@@ -89,8 +207,8 @@ pub fn prepare_arguments_from_parts(
 fn prepare_arguments_internal(
     registry: &TaskRegistry,
     task_name: &str,
-    positional: Vec<String>,
-    mut named: IndexMap<String, String>,
+    positional: Vec<Dynamic>,
+    mut named: IndexMap<String, Dynamic>,
 ) -> Result<Vec<Dynamic>, Box<EvalAltResult>> {
     let task = registry
         .task(task_name)
@@ -115,12 +233,22 @@ fn prepare_arguments_internal(
     let mut positional_iter = positional.into_iter();
 
     for spec in &task.params {
-        if let Some(value) = named.shift_remove(&spec.name) {
-            values.push(Dynamic::from(ImmutableString::from(value)));
-        } else if let Some(value) = positional_iter.next() {
-            values.push(Dynamic::from(ImmutableString::from(value)));
+        let provided = named.shift_remove(&spec.name);
+        if spec.variadic && provided.is_none() {
+            let rest: Array = positional_iter.by_ref().collect();
+            if rest.is_empty() {
+                values.push(spec.default.clone().unwrap_or_default());
+            } else {
+                let value = coerce_argument(&spec.name, &spec.ty, rest.into())?;
+                ensure_choice(spec, &value)?;
+                values.push(value);
+            }
+        } else if let Some(value) = provided.or_else(|| positional_iter.next()) {
+            let value = coerce_argument(&spec.name, &spec.ty, value)?;
+            ensure_choice(spec, &value)?;
+            values.push(value);
         } else if let Some(default) = &spec.default {
-            values.push(Dynamic::from(ImmutableString::from(default.clone())));
+            values.push(default.clone());
         } else {
             return Err(context_error(format!(
                 "Argument '{}' is missing.",
@@ -148,6 +276,112 @@ fn prepare_arguments_internal(
     Ok(values)
 }
 
+/// Rejects `value` when the parameter restricts itself to `choices`.
+pub(crate) fn ensure_choice(
+    spec: &ParameterSpec,
+    value: &Dynamic,
+) -> Result<(), Box<EvalAltResult>> {
+    if spec.choices.is_empty() {
+        return Ok(());
+    }
+    if let Some(items) = value.read_lock::<Array>() {
+        return items.iter().try_for_each(|item| ensure_choice(spec, item));
+    }
+    let text = value.to_string();
+    if spec.choices.iter().any(|choice| choice.to_string() == text) {
+        return Ok(());
+    }
+    let allowed: Vec<String> = spec
+        .choices
+        .iter()
+        .map(|choice| choice.to_string())
+        .collect();
+    Err(context_error(format!(
+        "Argument '{}' must be one of: {} (got '{}').",
+        spec.name,
+        allowed.join(", "),
+        text
+    )))
+}
+
+/// Converts one argument value into `ty`, reporting mismatches against the parameter `name`.
+pub(crate) fn coerce_argument(
+    name: &str,
+    ty: &ParamType,
+    value: Dynamic,
+) -> Result<Dynamic, Box<EvalAltResult>> {
+    if let Some(text) = value.clone().try_cast::<ImmutableString>() {
+        return parse_argument(name, ty, &text);
+    }
+    match ty {
+        ParamType::String => Ok(Dynamic::from(value.to_string())),
+        ParamType::Int if value.is_int() => Ok(value),
+        ParamType::Float if value.is_float() => Ok(value),
+        ParamType::Float if value.is_int() => Ok(Dynamic::from_float(
+            value.as_int().unwrap_or_default() as FLOAT,
+        )),
+        ParamType::Bool if value.is_bool() => Ok(value),
+        ParamType::Array(element) if value.is_array() => {
+            let items = value.cast::<Array>();
+            let converted = items
+                .into_iter()
+                .map(|item| coerce_argument(name, element, item))
+                .collect::<Result<Array, _>>()?;
+            Ok(converted.into())
+        }
+        ParamType::Array(element) => {
+            let item = coerce_argument(name, element, value)?;
+            Ok(Dynamic::from_array(vec![item]))
+        }
+        _ => Err(type_mismatch(name, ty, &value.to_string())),
+    }
+}
+
+fn parse_argument(name: &str, ty: &ParamType, text: &str) -> Result<Dynamic, Box<EvalAltResult>> {
+    match ty {
+        ParamType::String => Ok(Dynamic::from(text.to_string())),
+        ParamType::Int => text
+            .trim()
+            .parse::<INT>()
+            .map(Dynamic::from_int)
+            .map_err(|_| type_mismatch(name, ty, text)),
+        ParamType::Float => text
+            .trim()
+            .parse::<FLOAT>()
+            .map(Dynamic::from_float)
+            .map_err(|_| type_mismatch(name, ty, text)),
+        ParamType::Bool => match text.trim().to_ascii_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Ok(Dynamic::TRUE),
+            "false" | "no" | "off" | "0" => Ok(Dynamic::FALSE),
+            _ => Err(type_mismatch(name, ty, text)),
+        },
+        ParamType::Array(element) => {
+            if text.trim().is_empty() {
+                return Ok(Dynamic::from_array(Array::new()));
+            }
+            let items = text
+                .split(',')
+                .map(|item| parse_argument(name, element, item.trim()))
+                .collect::<Result<Array, _>>()?;
+            Ok(items.into())
+        }
+    }
+}
+
+fn type_mismatch(name: &str, ty: &ParamType, got: &str) -> Box<EvalAltResult> {
+    let expected = match ty {
+        ParamType::String => "a string".to_string(),
+        ParamType::Int => "an integer".to_string(),
+        ParamType::Float => "a number".to_string(),
+        ParamType::Bool => "a boolean (true/false)".to_string(),
+        ParamType::Array(element) => format!("a comma-separated list of {}", element),
+    };
+    context_error(format!(
+        "Argument '{}' expects {}, got '{}'.",
+        name, expected, got
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,7 +409,7 @@ mod tests {
             "arm64".to_string(),
         ];
 
-        let (positional, named) = parse_cli_arguments(&raw).expect("parse args");
+        let (positional, named) = parse_cli_arguments(&raw, &[]).expect("parse args");
 
         assert_eq!(positional, vec!["release"]);
         assert_eq!(named.get("target"), Some(&"x86".to_string()));
@@ -183,6 +417,74 @@ mod tests {
         assert_eq!(named.get("arch"), Some(&"arm64".to_string()));
     }
 
+    #[test]
+    fn parse_cli_arguments_treats_boolean_params_as_switches() {
+        let mut quiet = ParameterSpec::new("quiet".into(), ParamType::Bool, None);
+        quiet.short = Some('q');
+        let mut color = ParameterSpec::new("color".into(), ParamType::Bool, None);
+        color.short = Some('c');
+        let params = vec![
+            ParameterSpec::new("verbose".into(), ParamType::Bool, None),
+            ParameterSpec::new("cache".into(), ParamType::Bool, None),
+            ParameterSpec::new("target".into(), ParamType::String, None),
+            quiet,
+            color,
+        ];
+        let raw: Vec<String> = [
+            "--verbose",
+            "release",
+            "--no-cache",
+            "-qc",
+            "--target",
+            "x86",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+
+        let (positional, named) = parse_cli_arguments(&raw, &params).expect("parse args");
+
+        assert_eq!(positional, vec!["release"]);
+        assert_eq!(named.get("verbose").map(String::as_str), Some("true"));
+        assert_eq!(named.get("cache").map(String::as_str), Some("false"));
+        assert_eq!(named.get("quiet").map(String::as_str), Some("true"));
+        assert_eq!(named.get("color").map(String::as_str), Some("true"));
+        assert_eq!(named.get("target").map(String::as_str), Some("x86"));
+    }
+
+    #[test]
+    fn parse_cli_arguments_accumulates_repeated_array_options() {
+        let mut feature = ParameterSpec::new(
+            "feature".into(),
+            ParamType::Array(Box::new(ParamType::String)),
+            None,
+        );
+        feature.short = Some('f');
+        let params = vec![
+            feature,
+            ParameterSpec::new("target".into(), ParamType::String, None),
+        ];
+        let raw: Vec<String> = ["--feature", "std", "--feature=serde,log", "-f", "rt"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+
+        let (_, named) = parse_cli_arguments(&raw, &params).expect("parse args");
+        assert_eq!(
+            named.get("feature").map(String::as_str),
+            Some("std,serde,log,rt")
+        );
+
+        let raw: Vec<String> = ["--target=x86", "target=arm"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let err = parse_cli_arguments(&raw, &params).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Argument 'target' was given more than once."));
+    }
+
     #[test]
     fn prepare_arguments_prioritizes_named_over_positional() {
         let registry = registry_with_args();
@@ -207,6 +509,112 @@ mod tests {
         );
     }
 
+    #[test]
+    fn prepare_arguments_converts_to_declared_types() {
+        let mut registry = TaskRegistry::new();
+        let mut stack = BuildStack::new();
+        stack.begin_task(&registry, "deploy").unwrap();
+        let mut ratio = Map::new();
+        ratio.insert("type".into(), Dynamic::from("float"));
+        let mut params = Map::new();
+        params.insert("retries".into(), Dynamic::from_int(3));
+        params.insert("verbose".into(), Dynamic::FALSE);
+        params.insert("ratio".into(), ratio.into());
+        stack.set_args(params).unwrap();
+        stack.end_task(&mut registry).unwrap();
+
+        let args = prepare_arguments_from_cli(
+            &registry,
+            "deploy",
+            &["0.5".to_string(), "--verbose=true".to_string()],
+        )
+        .expect("prepare args");
+        assert_eq!(args[0].as_float().ok(), Some(0.5));
+        assert_eq!(args[1].as_int().ok(), Some(3));
+        assert_eq!(args[2].as_bool().ok(), Some(true));
+
+        let err = prepare_arguments_from_cli(
+            &registry,
+            "deploy",
+            &["1".to_string(), "--retries=abc".to_string()],
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Argument 'retries' expects an integer, got 'abc'"));
+    }
+
+    #[test]
+    fn prepare_arguments_accepts_short_flags_and_checks_choices() {
+        let mut registry = TaskRegistry::new();
+        let mut stack = BuildStack::new();
+        stack.begin_task(&registry, "deploy").unwrap();
+        let mut options = Map::new();
+        options.insert("short".into(), Dynamic::from("t"));
+        options.insert(
+            "choices".into(),
+            Dynamic::from_array(vec!["staging".into(), "prod".into()]),
+        );
+        stack.add_arg("target", options).unwrap();
+        stack.end_task(&mut registry).unwrap();
+
+        let args = prepare_arguments_from_cli(&registry, "deploy", &["-t".into(), "prod".into()])
+            .expect("prepare args");
+        assert_eq!(args[0].clone().into_string().ok().as_deref(), Some("prod"));
+
+        let err = prepare_arguments_from_cli(&registry, "deploy", &["-t=dev".into()]).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Argument 'target' must be one of: staging, prod (got 'dev')"));
+    }
+
+    #[test]
+    fn prepare_arguments_collects_rest_into_variadic_array() {
+        let mut registry = TaskRegistry::new();
+        let mut stack = BuildStack::new();
+        stack.begin_task(&registry, "test").unwrap();
+        let mut rest = Map::new();
+        rest.insert("variadic".into(), Dynamic::TRUE);
+        stack.add_arg("package", Map::new()).unwrap();
+        stack.add_arg("filters", rest).unwrap();
+        assert!(stack.add_arg("late", Map::new()).is_err());
+        stack.end_task(&mut registry).unwrap();
+
+        let args = prepare_arguments_from_cli(
+            &registry,
+            "test",
+            &["core".into(), "parser".into(), "lexer".into()],
+        )
+        .expect("prepare args");
+        let filters: Vec<String> = args[1]
+            .clone()
+            .into_array()
+            .expect("array")
+            .into_iter()
+            .map(|item| item.into_string().expect("string"))
+            .collect();
+        assert_eq!(filters, vec!["parser", "lexer"]);
+
+        let args =
+            prepare_arguments_from_cli(&registry, "test", &["core".into()]).expect("prepare args");
+        assert!(args[1].clone().into_array().expect("array").is_empty());
+    }
+
+    #[test]
+    fn split_passthrough_stops_parsing_at_first_double_dash() {
+        let raw: Vec<String> = ["--mode=debug", "--", "--nocapture", "--", "-q"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let (args, passthrough) = split_passthrough(&raw);
+        assert_eq!(args, ["--mode=debug".to_string()]);
+        assert_eq!(passthrough, vec!["--nocapture", "--", "-q"]);
+
+        let (args, passthrough) = split_passthrough(&raw[..1]);
+        assert_eq!(args.len(), 1);
+        assert!(passthrough.is_empty());
+    }
+
     #[test]
     fn prepare_arguments_reports_unknown_keys() {
         let registry = registry_with_args();
@@ -1,4 +1,7 @@
-use crate::task::model::{Group, RegistryEntry};
+use indexmap::IndexMap;
+use std::path::PathBuf;
+
+use crate::task::model::{Group, RegistryEntry, SourceLocation};
 
 #[derive(Clone, Debug)]
 pub struct GroupBuilder {
@@ -18,6 +21,30 @@ impl GroupBuilder {
         self.group.description = Some(desc.to_string());
     }
 
+    pub fn set_working_dir(&mut self, dir: PathBuf) {
+        self.group.working_dir = Some(dir);
+    }
+
+    pub fn has_working_dir(&self) -> bool {
+        self.group.working_dir.is_some()
+    }
+
+    pub fn set_env(&mut self, env: IndexMap<String, String>) {
+        self.group.env = env;
+    }
+
+    pub fn has_env(&self) -> bool {
+        !self.group.env.is_empty()
+    }
+
+    pub fn set_declared_at(&mut self, location: SourceLocation) {
+        self.group.declared_at = Some(location);
+    }
+
+    pub fn declared_at(&self) -> Option<&SourceLocation> {
+        self.group.declared_at.as_ref()
+    }
+
     pub fn add_entry(&mut self, entry: RegistryEntry) {
         self.group.entries.push(entry);
     }
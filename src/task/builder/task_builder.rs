@@ -1,12 +1,14 @@
+use indexmap::IndexMap;
 use rhai::FnPtr;
 use std::path::PathBuf;
 
-use crate::task::model::{ParameterSpec, Task};
+use crate::task::model::{ParameterSpec, SourceLocation, Task, TaskAction};
 
 #[derive(Clone, Debug)]
 pub struct TaskBuilder {
     pub(crate) full_path: String,
     task: Task,
+    args_declared: bool,
 }
 
 impl TaskBuilder {
@@ -14,6 +16,7 @@ impl TaskBuilder {
         Self {
             full_path,
             task: Task::default(),
+            args_declared: false,
         }
     }
 
@@ -26,7 +29,7 @@ impl TaskBuilder {
     }
 
     pub fn set_actions(&mut self, func: FnPtr) {
-        self.task.actions = Some(func);
+        self.task.actions = Some(TaskAction::Script(func));
     }
 
     pub fn has_actions(&self) -> bool {
@@ -35,12 +38,26 @@ impl TaskBuilder {
 
     pub fn set_params(&mut self, params: Vec<ParameterSpec>) {
         self.task.params = params;
+        self.args_declared = true;
     }
 
     pub fn has_params(&self) -> bool {
         !self.task.params.is_empty()
     }
 
+    /// Whether parameters came from `args()` rather than individual `arg()` calls.
+    pub fn has_args_map(&self) -> bool {
+        self.args_declared
+    }
+
+    pub fn add_param(&mut self, param: ParameterSpec) {
+        self.task.params.push(param);
+    }
+
+    pub fn params(&self) -> &[ParameterSpec] {
+        &self.task.params
+    }
+
     pub fn set_working_dir(&mut self, dir: PathBuf) {
         self.task.working_dir = Some(dir);
     }
@@ -49,6 +66,58 @@ impl TaskBuilder {
         self.task.working_dir.is_some()
     }
 
+    pub fn set_env(&mut self, env: IndexMap<String, String>) {
+        self.task.env = env;
+    }
+
+    pub fn has_env(&self) -> bool {
+        !self.task.env.is_empty()
+    }
+
+    pub fn set_deps(&mut self, deps: Vec<String>) {
+        self.task.deps = deps;
+    }
+
+    pub fn has_deps(&self) -> bool {
+        !self.task.deps.is_empty()
+    }
+
+    pub fn set_sources(&mut self, patterns: Vec<String>) {
+        self.task.sources = patterns;
+    }
+
+    pub fn has_sources(&self) -> bool {
+        !self.task.sources.is_empty()
+    }
+
+    pub fn set_outputs(&mut self, patterns: Vec<String>) {
+        self.task.outputs = patterns;
+    }
+
+    pub fn has_outputs(&self) -> bool {
+        !self.task.outputs.is_empty()
+    }
+
+    pub fn enable_fingerprint(&mut self) {
+        self.task.fingerprint = true;
+    }
+
+    pub fn has_fingerprint(&self) -> bool {
+        self.task.fingerprint
+    }
+
+    pub fn set_source_text(&mut self, text: String) {
+        self.task.source_text = Some(text);
+    }
+
+    pub fn set_declared_at(&mut self, location: SourceLocation) {
+        self.task.declared_at = Some(location);
+    }
+
+    pub fn declared_at(&self) -> Option<&SourceLocation> {
+        self.task.declared_at.as_ref()
+    }
+
     pub fn build(self) -> (String, Task) {
         (self.full_path, self.task)
     }
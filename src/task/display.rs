@@ -1,5 +1,8 @@
-use super::model::{leaf_name, RegistryEntry};
-use super::registry::TaskRegistry;
+use std::path::Path;
+
+use super::model::{is_path_suffix, ParameterSpec, SourceLocation};
+use super::query::TaskInfo;
+use super::registry::{Freshness, TaskRegistry};
 use crate::logger::trace;
 
 #[derive(Debug, Default, Clone)]
@@ -28,26 +31,19 @@ pub struct ListItem {
     pub name: String,
     pub full_name: String,
     pub description: Option<String>,
+    /// Extra lines printed beneath the item, such as effective `dir()`/`env()` values.
+    pub settings: Vec<String>,
 }
 
 impl ListItem {
-    fn group(depth: usize, full_name: String, description: Option<String>) -> Self {
+    fn from_info(depth: usize, info: &TaskInfo) -> Self {
         Self {
-            kind: ListItemKind::Group,
+            kind: info.kind,
             depth,
-            name: leaf_name(&full_name).to_string(),
-            full_name,
-            description,
-        }
-    }
-
-    fn task(depth: usize, full_name: String, description: Option<String>) -> Self {
-        Self {
-            kind: ListItemKind::Task,
-            depth,
-            name: leaf_name(&full_name).to_string(),
-            full_name,
-            description,
+            name: info.name.clone(),
+            full_name: info.full_path.clone(),
+            description: info.description.clone(),
+            settings: Vec::new(),
         }
     }
 }
@@ -84,7 +80,109 @@ impl TaskRegistry {
         crate::printer::print_list(&output, mode);
     }
 
-    fn collect_list_output(&self, group: Option<&str>) -> ListOutput {
+    /// Prints the task tree annotated with whether each task is up to date, stale, or
+    /// has never run. Changed files are shown relative to `script_root`.
+    pub fn status(
+        &self,
+        group: Option<&str>,
+        script_root: Option<&Path>,
+        cache_dir: Option<&Path>,
+    ) {
+        let output = self.collect_status_output(group, script_root, cache_dir);
+        crate::printer::print_list(&output, ListRenderMode::Tree);
+    }
+
+    /// Prints the task tree with each task's effective working directory and environment,
+    /// including values inherited from enclosing groups.
+    pub fn list_settings(
+        &self,
+        group: Option<&str>,
+        mode: ListRenderMode,
+        script_root: Option<&Path>,
+    ) {
+        let output = self.collect_settings_output(group, script_root);
+        crate::printer::print_list(&output, mode);
+    }
+
+    /// Prints everything known about one task: where it was declared, its parameters in
+    /// positional order, its dependencies, and its effective `dir()`/`env()`.
+    pub fn show(&self, full_path: &str, script_root: Option<&Path>) {
+        let output = self.collect_task_details(full_path, script_root);
+        crate::printer::print_list(&output, ListRenderMode::Flat);
+    }
+
+    fn collect_task_details(&self, full_path: &str, script_root: Option<&Path>) -> ListOutput {
+        let mut output = ListOutput::default();
+        let Some(task) = self
+            .info(full_path)
+            .filter(|info| info.kind == ListItemKind::Task)
+        else {
+            output.push_message(
+                ListMessageLevel::Error,
+                format!("Task '{}' does not exist.", full_path),
+            );
+            return output;
+        };
+        let mut item = ListItem::from_info(0, &task);
+        if let Some(location) = &task.declared_at {
+            item.settings.push(declared_at_line(location, script_root));
+        }
+        if task.params.is_empty() {
+            item.settings.push("args: none".to_string());
+        } else {
+            item.settings.push("args:".to_string());
+            for (index, spec) in task.params.iter().enumerate() {
+                item.settings.push(describe_param(index + 1, spec));
+            }
+        }
+        if !task.deps.is_empty() {
+            item.settings
+                .push(format!("deps: {}", task.deps.join(", ")));
+        }
+        item.settings.extend(settings_lines(&task, script_root));
+        output.push_item(item);
+        output
+    }
+
+    fn collect_settings_output(
+        &self,
+        group: Option<&str>,
+        script_root: Option<&Path>,
+    ) -> ListOutput {
+        let mut output = self.collect_list_output(group);
+        for item in output.items.iter_mut() {
+            let Some(info) = self.info(&item.full_name) else {
+                continue;
+            };
+            if info.kind == ListItemKind::Task {
+                item.settings = settings_lines(&info, script_root);
+            }
+            if let Some(location) = &info.declared_at {
+                item.settings.push(declared_at_line(location, script_root));
+            }
+        }
+        output
+    }
+
+    fn collect_status_output(
+        &self,
+        group: Option<&str>,
+        script_root: Option<&Path>,
+        cache_dir: Option<&Path>,
+    ) -> ListOutput {
+        let mut output = self.collect_list_output(group);
+        for item in output
+            .items
+            .iter_mut()
+            .filter(|item| item.kind == ListItemKind::Task)
+        {
+            let freshness = self.freshness(&item.full_name, cache_dir);
+            item.description = Some(status_label(&freshness, script_root));
+        }
+        output
+    }
+
+    pub(super) fn collect_list_output(&self, group: Option<&str>) -> ListOutput {
         let mut output = ListOutput::default();
 
         if let Some(path) = group {
@@ -92,7 +190,7 @@ impl TaskRegistry {
             match self.resolve_group(path) {
                 GroupLookup::Found(full_path) => {
                     trace!("resolved group '{}' -> '{}'", path, full_path);
-                    self.collect_group(&full_path, 0, &mut output);
+                    self.collect_entry(&full_path, 0, &mut output);
                 }
                 GroupLookup::Ambiguous(paths) => {
                     output.push_message(
@@ -117,55 +215,110 @@ impl TaskRegistry {
             return output;
         }
 
-        if self.root_entries().is_empty() {
-            for (full_path, task) in self.tasks_iter() {
-                output.push_item(ListItem::task(
-                    0,
-                    full_path.to_string(),
-                    task.description.clone(),
-                ));
-            }
-            return output;
-        }
-
-        for entry in self.root_entries() {
-            self.collect_entry(entry, 0, &mut output);
+        for full_path in self.roots() {
+            self.collect_entry(&full_path, 0, &mut output);
         }
 
         output
     }
 
-    fn collect_entry(&self, entry: &RegistryEntry, depth: usize, output: &mut ListOutput) {
-        match entry {
-            RegistryEntry::Task(full_path) => self.collect_task(full_path, depth, output),
-            RegistryEntry::Group(full_path) => self.collect_group(full_path, depth, output),
+    fn collect_entry(&self, full_path: &str, depth: usize, output: &mut ListOutput) {
+        let Some(info) = self.info(full_path) else {
+            return;
+        };
+        output.push_item(ListItem::from_info(depth, &info));
+        for child in &info.children {
+            self.collect_entry(child, depth + 1, output);
         }
     }
+}
 
-    fn collect_task(&self, full_path: &str, depth: usize, output: &mut ListOutput) {
-        if let Some(task) = self.task(full_path) {
-            output.push_item(ListItem::task(
-                depth,
-                full_path.to_string(),
-                task.description.clone(),
-            ));
-        } else {
-            output.push_item(ListItem::task(depth, full_path.to_string(), None));
-        }
+/// `dir: ...` and `env: K=V ...` lines for the effective settings of a task.
+fn settings_lines(info: &TaskInfo, script_root: Option<&Path>) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(dir) = &info.dir {
+        lines.push(format!("dir: {}", display_path(dir, script_root)));
+    }
+    if !info.env.is_empty() {
+        let vars: Vec<String> = info
+            .env
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        lines.push(format!("env: {}", vars.join(" ")));
     }
+    lines
+}
 
-    fn collect_group(&self, full_path: &str, depth: usize, output: &mut ListOutput) {
-        if let Some(group) = self.group(full_path) {
-            output.push_item(ListItem::group(
-                depth,
-                full_path.to_string(),
-                group.description.clone(),
-            ));
-            for entry in &group.entries {
-                self.collect_entry(entry, depth + 1, output);
+/// One parameter as `  1. name (type), required|default: ..., short, choices - help`.
+fn describe_param(position: usize, spec: &ParameterSpec) -> String {
+    let variadic = if spec.variadic { "..." } else { "" };
+    let mut line = format!("  {}. {}{} ({})", position, spec.name, variadic, spec.ty);
+    match &spec.default {
+        Some(default) => line.push_str(&format!(", default: {:?}", default)),
+        None => line.push_str(", required"),
+    }
+    if let Some(short) = spec.short {
+        line.push_str(&format!(", -{}", short));
+    }
+    if !spec.choices.is_empty() {
+        let choices: Vec<String> = spec.choices.iter().map(ToString::to_string).collect();
+        line.push_str(&format!(", one of: {}", choices.join(", ")));
+    }
+    if let Some(help) = &spec.help {
+        line.push_str(&format!(" - {}", help));
+    }
+    line
+}
+
+/// `declared at: file:line:column`, with the file relative to `script_root`.
+fn declared_at_line(location: &SourceLocation, script_root: Option<&Path>) -> String {
+    match &location.file {
+        Some(file) => format!(
+            "declared at: {}:{}:{}",
+            display_path(file, script_root),
+            location.line,
+            location.column
+        ),
+        None => format!("declared at: {}", location),
+    }
+}
+
+/// `path` relative to `script_root` when it lies inside it (`.` for the root itself).
+fn display_path(path: &Path, script_root: Option<&Path>) -> String {
+    script_root
+        .and_then(|root| path.strip_prefix(root).ok())
+        .map(|relative| {
+            if relative.as_os_str().is_empty() {
+                ".".to_string()
+            } else {
+                relative.display().to_string()
             }
-        } else {
-            output.push_item(ListItem::group(depth, full_path.to_string(), None));
+        })
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+fn status_label(freshness: &Freshness, script_root: Option<&Path>) -> String {
+    match freshness {
+        Freshness::Untracked => "always runs".to_string(),
+        Freshness::NeverRun => "never run".to_string(),
+        Freshness::UpToDate => "up to date".to_string(),
+        Freshness::Stale { reason, changed } if changed.is_empty() => {
+            format!("stale ({})", reason)
+        }
+        Freshness::Stale { changed, .. } => {
+            let files: Vec<String> = changed
+                .iter()
+                .map(|file| {
+                    let path = Path::new(file);
+                    script_root
+                        .and_then(|root| path.strip_prefix(root).ok())
+                        .unwrap_or(path)
+                        .display()
+                        .to_string()
+                })
+                .collect();
+            format!("stale (changed: {})", files.join(", "))
         }
     }
 }
@@ -189,15 +342,10 @@ impl TaskRegistry {
             return GroupLookup::Found(trimmed.to_string());
         }
 
-        if trimmed.contains('.') {
-            trace!("group '{}' treated as dotted path but not found", trimmed);
-            return GroupLookup::NotFound;
-        }
-
         let matches: Vec<String> = self
             .groups_iter()
             .map(|(full_path, _)| full_path.clone())
-            .filter(|full_path| leaf_name(full_path) == trimmed)
+            .filter(|full_path| is_path_suffix(full_path, trimmed))
             .collect();
 
         match matches.len() {
@@ -227,6 +375,7 @@ impl TaskRegistry {
 mod tests {
     use super::*;
     use crate::task::stack::BuildStack;
+    use rhai::Dynamic;
 
     fn registry_with_sample_groups() -> TaskRegistry {
         let mut registry = TaskRegistry::new();
@@ -269,6 +418,113 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn collect_list_output_resolves_dotted_group_suffix() {
+        let mut registry = registry_with_sample_groups();
+        let mut stack = BuildStack::new();
+        stack.begin_group(&registry, "ci").unwrap();
+        stack.begin_group(&registry, "nightly").unwrap();
+        stack.begin_group(&registry, "full").unwrap();
+        stack.end_group(&mut registry).unwrap();
+        stack.end_group(&mut registry).unwrap();
+        stack.end_group(&mut registry).unwrap();
+
+        let output = registry.collect_list_output(Some("nightly.full"));
+        assert!(output.messages.is_empty());
+        assert_eq!(output.items[0].full_name, "ci.nightly.full");
+        let output = registry.collect_list_output(Some("ops.release"));
+        assert_eq!(output.items[0].full_name, "ops.release");
+        let output = registry.collect_list_output(Some("s.release"));
+        assert!(output
+            .messages
+            .iter()
+            .any(|msg| msg.text.contains("does not exist")));
+    }
+
+    #[test]
+    fn collect_status_output_labels_tasks() {
+        let registry = registry_with_sample_groups();
+        let output = registry.collect_status_output(Some("ops"), None, None);
+        let deploy = output
+            .items
+            .iter()
+            .find(|item| item.full_name == "ops.release.deploy")
+            .expect("deploy listed");
+        assert_eq!(deploy.description.as_deref(), Some("always runs"));
+        let group = output
+            .items
+            .iter()
+            .find(|item| item.full_name == "ops")
+            .expect("group listed");
+        assert_eq!(group.description.as_deref(), Some("operations"));
+    }
+
+    #[test]
+    fn collect_settings_output_shows_inherited_values() {
+        let mut registry = TaskRegistry::new();
+        let mut stack = BuildStack::new();
+        let mut env = rhai::Map::new();
+        env.insert("MODE".into(), "web".into());
+
+        stack.begin_group(&registry, "frontend").unwrap();
+        stack.set_env(env).unwrap();
+        stack.begin_task(&registry, "serve").unwrap();
+        stack.end_task(&mut registry).unwrap();
+        stack.end_group(&mut registry).unwrap();
+
+        let output = registry.collect_settings_output(None, None);
+        let serve = output
+            .items
+            .iter()
+            .find(|item| item.full_name == "frontend.serve")
+            .expect("serve listed");
+        assert_eq!(serve.settings, vec!["env: MODE=web".to_string()]);
+    }
+
+    #[test]
+    fn collect_task_details_lists_params_in_positional_order() {
+        let mut registry = TaskRegistry::new();
+        let mut stack = BuildStack::new();
+        stack.begin_group(&registry, "ops").unwrap();
+        stack.begin_task(&registry, "deploy").unwrap();
+        let mut target = rhai::Map::new();
+        target.insert("help".into(), "Where to deploy".into());
+        target.insert("short".into(), "t".into());
+        target.insert(
+            "choices".into(),
+            Dynamic::from_array(vec!["staging".into(), "prod".into()]),
+        );
+        stack.add_arg("target", target).unwrap();
+        let mut retries = rhai::Map::new();
+        retries.insert("default".into(), Dynamic::from_int(3));
+        stack.add_arg("retries", retries).unwrap();
+        stack.end_task(&mut registry).unwrap();
+        stack.end_group(&mut registry).unwrap();
+
+        let output = registry.collect_task_details("ops.deploy", None);
+        assert_eq!(
+            output.items[0].settings,
+            vec![
+                "args:".to_string(),
+                "  1. target (string), required, -t, one of: staging, prod - Where to deploy"
+                    .to_string(),
+                "  2. retries (int), default: 3".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn status_label_lists_changed_files_relative_to_root() {
+        let freshness = Freshness::Stale {
+            reason: String::new(),
+            changed: vec!["/work/proto/a.proto".to_string()],
+        };
+        assert_eq!(
+            status_label(&freshness, Some(Path::new("/work"))),
+            "stale (changed: proto/a.proto)"
+        );
+    }
+
     #[test]
     fn collect_list_output_warns_when_group_missing() {
         let registry = registry_with_sample_groups();
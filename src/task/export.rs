@@ -0,0 +1,360 @@
+use indexmap::IndexMap;
+use rhai::{Array, Dynamic};
+use serde::Serialize;
+use serde_json::{Number, Value};
+
+use super::display::{ListItem, ListItemKind, ListMessage, ListMessageLevel};
+use super::model::ParameterSpec;
+use super::query::TaskInfo;
+use super::registry::{TaskLookup, TaskRegistry};
+
+/// Machine-readable encodings for `rhask list --format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Yaml,
+    Tsv,
+}
+
+/// The task tree of a `ListOutput` with full details, as serialized by `--format`.
+#[derive(Debug, Clone, Default)]
+pub struct ListDocument {
+    /// Full path of the `default_task()`, if one is declared.
+    pub default_task: Option<String>,
+    pub entries: Vec<ListEntry>,
+    /// Warnings such as an unknown or ambiguous group, kept out of stderr.
+    pub messages: Vec<ListMessage>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ListEntry {
+    pub info: TaskInfo,
+    /// Children of a group, in declaration order.
+    pub entries: Vec<ListEntry>,
+}
+
+impl TaskRegistry {
+    /// Prints the task tree (or one group of it) in a machine-readable `format`.
+    pub fn export(&self, group: Option<&str>, format: ExportFormat) {
+        let document = self.collect_document(group);
+        crate::printer::info(document.render(format));
+    }
+
+    pub(crate) fn collect_document(&self, group: Option<&str>) -> ListDocument {
+        let output = self.collect_list_output(group);
+        let default_task = self
+            .default_task()
+            .map(|name| match self.resolve_task(&name) {
+                TaskLookup::Found { full_path } => full_path,
+                _ => name,
+            });
+        ListDocument {
+            default_task,
+            entries: self.nest_items(&output.items),
+            messages: output.messages,
+        }
+    }
+
+    /// Rebuilds the tree from depth-first `items`, where children follow their group.
+    fn nest_items(&self, items: &[ListItem]) -> Vec<ListEntry> {
+        let mut entries = Vec::new();
+        let mut index = 0;
+        while index < items.len() {
+            let depth = items[index].depth;
+            let end = items[index + 1..]
+                .iter()
+                .position(|item| item.depth <= depth)
+                .map_or(items.len(), |offset| index + 1 + offset);
+            if let Some(info) = self.info(&items[index].full_name) {
+                entries.push(ListEntry {
+                    info,
+                    entries: self.nest_items(&items[index + 1..end]),
+                });
+            }
+            index = end;
+        }
+        entries
+    }
+}
+
+impl ListDocument {
+    /// Serializes the document; JSON and YAML share one schema, TSV has one row per entry.
+    pub fn render(&self, format: ExportFormat) -> String {
+        let view = DocumentView::new(self);
+        match format {
+            ExportFormat::Json => {
+                serde_json::to_string_pretty(&view).expect("list documents serialize to JSON")
+            }
+            ExportFormat::Yaml => serde_yaml::to_string(&view)
+                .expect("list documents serialize to YAML")
+                .trim_end()
+                .to_string(),
+            ExportFormat::Tsv => render_tsv(self),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct DocumentView<'a> {
+    default_task: Option<&'a str>,
+    entries: Vec<EntryView<'a>>,
+    messages: Vec<MessageView<'a>>,
+}
+
+impl<'a> DocumentView<'a> {
+    fn new(document: &'a ListDocument) -> Self {
+        let default_task = document.default_task.as_deref();
+        Self {
+            default_task,
+            entries: document
+                .entries
+                .iter()
+                .map(|entry| EntryView::new(entry, default_task))
+                .collect(),
+            messages: document
+                .messages
+                .iter()
+                .map(|message| MessageView {
+                    level: level_name(message.level),
+                    text: &message.text,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct MessageView<'a> {
+    level: &'static str,
+    text: &'a str,
+}
+
+/// Tasks carry `default`, `deps` and `params`; groups carry their nested `entries`.
+#[derive(Serialize)]
+struct EntryView<'a> {
+    kind: &'static str,
+    name: &'a str,
+    full_name: &'a str,
+    description: Option<&'a str>,
+    dir: Option<String>,
+    env: &'a IndexMap<String, String>,
+    declared_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deps: Option<&'a [String]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    params: Option<Vec<ParamView<'a>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entries: Option<Vec<EntryView<'a>>>,
+}
+
+impl<'a> EntryView<'a> {
+    fn new(entry: &'a ListEntry, default_task: Option<&str>) -> Self {
+        let info = &entry.info;
+        let is_task = info.kind == ListItemKind::Task;
+        Self {
+            kind: kind_name(info.kind),
+            name: &info.name,
+            full_name: &info.full_path,
+            description: info.description.as_deref(),
+            dir: info.dir.as_ref().map(|dir| dir.display().to_string()),
+            env: &info.env,
+            declared_at: info.declared_at.as_ref().map(ToString::to_string),
+            default: is_task.then(|| default_task == Some(info.full_path.as_str())),
+            deps: is_task.then_some(info.deps.as_slice()),
+            params: is_task.then(|| info.params.iter().map(ParamView::new).collect()),
+            entries: (!is_task).then(|| {
+                entry
+                    .entries
+                    .iter()
+                    .map(|child| EntryView::new(child, default_task))
+                    .collect()
+            }),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ParamView<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    ty: String,
+    required: bool,
+    default: Value,
+    help: Option<&'a str>,
+    short: Option<char>,
+    choices: Vec<Value>,
+    variadic: bool,
+}
+
+impl<'a> ParamView<'a> {
+    fn new(spec: &'a ParameterSpec) -> Self {
+        Self {
+            name: &spec.name,
+            ty: spec.ty.to_string(),
+            required: spec.default.is_none(),
+            default: spec.default.as_ref().map_or(Value::Null, dynamic_value),
+            help: spec.help.as_deref(),
+            short: spec.short,
+            choices: spec.choices.iter().map(dynamic_value).collect(),
+            variadic: spec.variadic,
+        }
+    }
+}
+
+/// JSON has no infinity or NaN, so non-finite floats are written as `null`.
+fn dynamic_value(value: &Dynamic) -> Value {
+    if let Ok(flag) = value.as_bool() {
+        Value::Bool(flag)
+    } else if let Ok(int) = value.as_int() {
+        Value::from(int)
+    } else if let Ok(float) = value.as_float() {
+        Number::from_f64(float).map_or(Value::Null, Value::Number)
+    } else if let Some(items) = value.read_lock::<Array>() {
+        Value::Array(items.iter().map(dynamic_value).collect())
+    } else if value.is_unit() {
+        Value::Null
+    } else {
+        Value::String(value.to_string())
+    }
+}
+
+fn kind_name(kind: ListItemKind) -> &'static str {
+    match kind {
+        ListItemKind::Group => "group",
+        ListItemKind::Task => "task",
+    }
+}
+
+fn level_name(level: ListMessageLevel) -> &'static str {
+    match level {
+        ListMessageLevel::Info => "info",
+        ListMessageLevel::Warn => "warn",
+        ListMessageLevel::Error => "error",
+    }
+}
+
+fn render_tsv(document: &ListDocument) -> String {
+    let mut rows = vec!["kind\tfull_name\tdescription\tparams\tdir\tdefault".to_string()];
+    for message in &document.messages {
+        rows.push(format!(
+            "{}\t\t{}\t\t\t",
+            level_name(message.level),
+            tsv_field(&message.text)
+        ));
+    }
+    let mut pending: Vec<&ListEntry> = document.entries.iter().rev().collect();
+    while let Some(entry) = pending.pop() {
+        let info = &entry.info;
+        let params: Vec<String> = info.params.iter().map(tsv_param).collect();
+        let is_default = document.default_task.as_deref() == Some(info.full_path.as_str());
+        rows.push(format!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            kind_name(info.kind),
+            tsv_field(&info.full_path),
+            tsv_field(info.description.as_deref().unwrap_or_default()),
+            tsv_field(&params.join(",")),
+            tsv_field(
+                &info
+                    .dir
+                    .as_ref()
+                    .map(|dir| dir.display().to_string())
+                    .unwrap_or_default()
+            ),
+            if is_default { "yes" } else { "" }
+        ));
+        pending.extend(entry.entries.iter().rev());
+    }
+    rows.join("\n")
+}
+
+/// `name:type`, plus `=default` when the parameter is optional.
+fn tsv_param(spec: &ParameterSpec) -> String {
+    let variadic = if spec.variadic { "..." } else { "" };
+    match &spec.default {
+        Some(default) => format!("{}{}:{}={}", spec.name, variadic, spec.ty, default),
+        None => format!("{}{}:{}", spec.name, variadic, spec.ty),
+    }
+}
+
+fn tsv_field(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::stack::BuildStack;
+
+    fn registry_with_release_task() -> TaskRegistry {
+        let mut registry = TaskRegistry::new();
+        let mut stack = BuildStack::new();
+        stack.begin_group(&registry, "build").unwrap();
+        stack.set_description("build \"things\"").unwrap();
+        stack.begin_task(&registry, "release").unwrap();
+        let mut profile = rhai::Map::new();
+        profile.insert("default".into(), Dynamic::from("debug"));
+        stack.add_arg("profile", profile).unwrap();
+        stack.end_task(&mut registry).unwrap();
+        stack.end_group(&mut registry).unwrap();
+        registry
+    }
+
+    #[test]
+    fn collect_document_nests_entries_and_keeps_messages() {
+        let registry = registry_with_release_task();
+        let document = registry.collect_document(None);
+        assert_eq!(document.entries.len(), 1);
+        assert_eq!(document.entries[0].info.kind, ListItemKind::Group);
+        assert_eq!(
+            document.entries[0].entries[0].info.full_path,
+            "build.release"
+        );
+        assert!(document.messages.is_empty());
+
+        let missing = registry.collect_document(Some("missing"));
+        assert!(missing.entries.is_empty());
+        assert_eq!(missing.messages[0].level, ListMessageLevel::Warn);
+    }
+
+    #[test]
+    fn render_escapes_values_in_every_format() {
+        let document = registry_with_release_task().collect_document(None);
+
+        let json = document.render(ExportFormat::Json);
+        assert!(json.contains(r#""description": "build \"things\"""#));
+        assert!(json.contains(r#""default": "debug""#));
+        assert!(json.contains(r#""required": false"#));
+
+        let yaml = document.render(ExportFormat::Yaml);
+        assert!(yaml.starts_with("default_task: null\nentries:\n- kind: group\n"));
+        assert!(yaml.contains("\n  - kind: task\n"));
+
+        let tsv = document.render(ExportFormat::Tsv);
+        let rows: Vec<&str> = tsv.lines().collect();
+        assert_eq!(
+            rows[0],
+            "kind\tfull_name\tdescription\tparams\tdir\tdefault"
+        );
+        assert_eq!(rows[2], "task\tbuild.release\t\tprofile:string=debug\t\t");
+    }
+
+    #[test]
+    fn render_writes_non_finite_defaults_as_null() {
+        let mut registry = TaskRegistry::new();
+        let mut stack = BuildStack::new();
+        stack.begin_task(&registry, "scale").unwrap();
+        let mut ratio = rhai::Map::new();
+        ratio.insert("default".into(), Dynamic::from_float(f64::INFINITY));
+        stack.add_arg("ratio", ratio).unwrap();
+        stack.end_task(&mut registry).unwrap();
+
+        let json = registry.collect_document(None).render(ExportFormat::Json);
+        let parsed: Value = serde_json::from_str(&json).expect("valid JSON");
+        assert_eq!(parsed["entries"][0]["params"][0]["default"], Value::Null);
+        assert_eq!(parsed["entries"][0]["params"][0]["type"], "float");
+    }
+}
@@ -1,13 +1,20 @@
 mod arguments;
 mod builder;
 mod display;
+mod export;
 mod model;
+mod query;
 mod registry;
 mod stack;
 
-pub use arguments::{prepare_arguments_from_cli, prepare_arguments_from_parts};
+pub use arguments::{prepare_arguments_from_cli, prepare_arguments_from_parts, split_passthrough};
 pub use display::{
     ListItem, ListItemKind, ListMessage, ListMessageLevel, ListOutput, ListRenderMode,
 };
+pub use export::{ExportFormat, ListDocument, ListEntry};
+pub(crate) use model::Task;
+pub use model::{NativeAction, ParamType, ParameterSpec, SourceLocation, TaskAction};
+pub use query::TaskInfo;
+pub(crate) use registry::{EffectiveSettings, PendingRun};
 pub use registry::{TaskLookup, TaskRegistry};
 pub(crate) use stack::BuildStack;
@@ -1,7 +1,16 @@
+use indexmap::IndexMap;
+use std::path::PathBuf;
+
+use super::task::SourceLocation;
+
 #[derive(Clone, Default, Debug)]
 pub struct Group {
     pub description: Option<String>,
+    pub working_dir: Option<PathBuf>,
+    pub env: IndexMap<String, String>,
     pub entries: Vec<RegistryEntry>,
+    /// Where the `group(...)` call appears.
+    pub declared_at: Option<SourceLocation>,
 }
 
 #[derive(Clone, Debug)]
@@ -3,8 +3,8 @@ mod task;
 mod util;
 
 pub use group::{Group, RegistryEntry};
-pub use task::{ParameterSpec, Task};
-pub(crate) use util::{context_error, leaf_name};
+pub use task::{NativeAction, ParamType, ParameterSpec, SourceLocation, Task, TaskAction};
+pub(crate) use util::{context_error, is_path_suffix, leaf_name};
 
 #[cfg(test)]
 mod tests;
@@ -1,16 +1,159 @@
-use rhai::FnPtr;
+use indexmap::IndexMap;
+use rhai::{Dynamic, FnPtr};
+use std::error::Error;
+use std::fmt;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 #[derive(Clone, Default, Debug)]
 pub struct Task {
     pub description: Option<String>,
-    pub actions: Option<FnPtr>,
+    pub actions: Option<TaskAction>,
     pub params: Vec<ParameterSpec>,
     pub working_dir: Option<PathBuf>,
+    pub env: IndexMap<String, String>,
+    pub deps: Vec<String>,
+    pub sources: Vec<String>,
+    pub outputs: Vec<String>,
+    pub fingerprint: bool,
+    pub source_text: Option<String>,
+    /// Where the `task(...)` call appears.
+    pub declared_at: Option<SourceLocation>,
+}
+
+/// Body of a task implemented in Rust. It receives the resolved arguments in positional
+/// order; an error fails the task like a `throw` in `actions()` would.
+pub type NativeAction =
+    Arc<dyn Fn(&[Dynamic]) -> Result<(), Box<dyn Error + Send + Sync>> + Send + Sync>;
+
+/// What runs when a task is invoked.
+#[derive(Clone)]
+pub enum TaskAction {
+    /// The closure given to `actions()` in the rhaskfile.
+    Script(FnPtr),
+    /// A closure registered with `ScriptEngine::register_native_task`.
+    Native(NativeAction),
+}
+
+impl fmt::Debug for TaskAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskAction::Script(func) => f.debug_tuple("Script").field(func).finish(),
+            TaskAction::Native(_) => f.write_str("Native"),
+        }
+    }
+}
+
+/// Position of a `task(...)`/`group(...)` call, as reported by Rhai.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// The rhaskfile or imported module; `None` for scripts not loaded from a file.
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}:{}:{}", file.display(), self.line, self.column),
+            None => write!(f, "line {}, column {}", self.line, self.column),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct ParameterSpec {
     pub name: String,
-    pub default: Option<String>,
+    pub ty: ParamType,
+    pub default: Option<Dynamic>,
+    pub help: Option<String>,
+    /// Allowed values, already converted to `ty`; empty means anything goes.
+    pub choices: Vec<Dynamic>,
+    pub short: Option<char>,
+    /// Collects every remaining positional into an array; always the last parameter.
+    pub variadic: bool,
+}
+
+impl ParameterSpec {
+    pub fn new(name: String, ty: ParamType, default: Option<Dynamic>) -> Self {
+        Self {
+            name,
+            ty,
+            default,
+            help: None,
+            choices: Vec::new(),
+            short: None,
+            variadic: false,
+        }
+    }
+}
+
+/// Rhai type an argument is converted to before it reaches `actions()`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ParamType {
+    #[default]
+    String,
+    Int,
+    Float,
+    Bool,
+    Array(Box<ParamType>),
+}
+
+impl ParamType {
+    /// Type implied by a default value; arrays take their element type from the first item.
+    pub fn infer(value: &Dynamic) -> Option<Self> {
+        if value.is_string() {
+            Some(Self::String)
+        } else if value.is_int() {
+            Some(Self::Int)
+        } else if value.is_float() {
+            Some(Self::Float)
+        } else if value.is_bool() {
+            Some(Self::Bool)
+        } else if value.is_array() {
+            let element = value
+                .read_lock::<rhai::Array>()
+                .and_then(|items| items.first().and_then(Self::infer))
+                .filter(|element| !matches!(element, Self::Array(_)))
+                .unwrap_or_default();
+            Some(Self::Array(Box::new(element)))
+        } else {
+            None
+        }
+    }
+
+    /// Parses an explicit `type:` name such as `"int"` or `"array<float>"`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        if let Some(inner) = name
+            .strip_prefix("array<")
+            .and_then(|rest| rest.strip_suffix('>'))
+        {
+            return match Self::from_name(inner)? {
+                Self::Array(_) => None,
+                element => Some(Self::Array(Box::new(element))),
+            };
+        }
+        match name {
+            "string" => Some(Self::String),
+            "int" => Some(Self::Int),
+            "float" => Some(Self::Float),
+            "bool" => Some(Self::Bool),
+            "array" => Some(Self::Array(Box::default())),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ParamType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String => f.write_str("string"),
+            Self::Int => f.write_str("int"),
+            Self::Float => f.write_str("float"),
+            Self::Bool => f.write_str("bool"),
+            Self::Array(element) => write!(f, "array<{}>", element),
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use crate::task::{BuildStack, TaskLookup, TaskRegistry};
-use rhai::{Dynamic, FnPtr, Map};
+use rhai::{Dynamic, FnPtr, Map, Position};
 
 fn dummy_fn_ptr() -> FnPtr {
     FnPtr::new("dummy").unwrap()
@@ -62,6 +62,32 @@ fn reject_task_redefinition() {
     assert!(message.contains("is already defined"));
 }
 
+#[test]
+fn redefinition_error_points_to_first_declaration() {
+    let mut registry = TaskRegistry::new();
+    let mut stack = BuildStack::new();
+    stack.begin_group(&registry, "ops").unwrap();
+    stack.record_declaration(Some("lib/ops"), Position::new(2, 1));
+    stack.begin_task(&registry, "deploy").unwrap();
+    stack.record_declaration(Some("lib/ops"), Position::new(3, 5));
+    stack.end_task(&mut registry).unwrap();
+    stack.end_group(&mut registry).unwrap();
+
+    let deploy = registry.task("ops.deploy").expect("task registered");
+    let location = deploy.declared_at.as_ref().expect("position recorded");
+    assert_eq!((location.line, location.column), (3, 5));
+
+    let err = stack.begin_group(&registry, "ops").unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Group 'ops' is already defined at lib/ops:2:1."));
+
+    let err = stack.begin_task(&registry, "ops").unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Task 'ops' is already defined as a group at lib/ops:2:1."));
+}
+
 #[test]
 fn reject_group_redefinition_as_task() {
     let mut registry = TaskRegistry::new();
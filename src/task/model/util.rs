@@ -4,6 +4,13 @@ pub(crate) fn leaf_name(path: &str) -> &str {
     path.rsplit('.').next().unwrap_or(path)
 }
 
+/// Whether `identifier` names the trailing segments of `path`, so `release.deploy`
+/// matches `build.release.deploy` but not `build.prerelease.deploy`.
+pub(crate) fn is_path_suffix(path: &str, identifier: &str) -> bool {
+    path.strip_suffix(identifier)
+        .is_some_and(|rest| rest.is_empty() || rest.ends_with('.'))
+}
+
 pub(crate) fn context_error(msg: impl Into<String>) -> Box<EvalAltResult> {
     EvalAltResult::ErrorRuntime(msg.into().into(), Position::NONE).into()
 }
@@ -0,0 +1,135 @@
+use indexmap::IndexMap;
+use std::path::PathBuf;
+
+use super::display::ListItemKind;
+use super::model::{leaf_name, ParameterSpec, RegistryEntry, SourceLocation};
+use super::registry::TaskRegistry;
+
+/// Read-only view of one task or group, independent of how it is printed.
+#[derive(Debug, Clone)]
+pub struct TaskInfo {
+    pub kind: ListItemKind,
+    pub full_path: String,
+    /// Last segment of `full_path`.
+    pub name: String,
+    pub description: Option<String>,
+    /// Parameters in positional order; empty for groups.
+    pub params: Vec<ParameterSpec>,
+    /// Declared `deps()`; empty for groups.
+    pub deps: Vec<String>,
+    /// Effective `dir()`, including values inherited from enclosing groups.
+    pub dir: Option<PathBuf>,
+    /// Effective `env()`, including values inherited from enclosing groups.
+    pub env: IndexMap<String, String>,
+    pub declared_at: Option<SourceLocation>,
+    /// Full paths of a group's direct children in declaration order; empty for tasks.
+    pub children: Vec<String>,
+}
+
+impl TaskRegistry {
+    /// Full paths of the top-level tasks and groups in declaration order.
+    pub fn roots(&self) -> Vec<String> {
+        if self.root_entries().is_empty() {
+            return self.tasks_iter().map(|(path, _)| path.clone()).collect();
+        }
+        self.root_entries().iter().map(entry_path).collect()
+    }
+
+    /// Everything known about the task or group at `full_path`.
+    pub fn info(&self, full_path: &str) -> Option<TaskInfo> {
+        let mut settings = self.effective_settings(full_path);
+        if let Some(task) = self.task(full_path) {
+            return Some(TaskInfo {
+                kind: ListItemKind::Task,
+                full_path: full_path.to_string(),
+                name: leaf_name(full_path).to_string(),
+                description: task.description.clone(),
+                params: task.params.clone(),
+                deps: task.deps.clone(),
+                dir: settings.working_dir,
+                env: settings.env,
+                declared_at: task.declared_at.clone(),
+                children: Vec::new(),
+            });
+        }
+        let group = self.group(full_path)?;
+        if group.working_dir.is_some() {
+            settings.working_dir = group.working_dir.clone();
+        }
+        settings.env.extend(group.env.clone());
+        Some(TaskInfo {
+            kind: ListItemKind::Group,
+            full_path: full_path.to_string(),
+            name: leaf_name(full_path).to_string(),
+            description: group.description.clone(),
+            params: Vec::new(),
+            deps: Vec::new(),
+            dir: settings.working_dir,
+            env: settings.env,
+            declared_at: group.declared_at.clone(),
+            children: group.entries.iter().map(entry_path).collect(),
+        })
+    }
+
+    /// Every task and group, depth-first in declaration order.
+    pub fn entries(&self) -> Vec<TaskInfo> {
+        let mut entries = Vec::new();
+        let mut pending: Vec<String> = self.roots().into_iter().rev().collect();
+        while let Some(full_path) = pending.pop() {
+            let Some(info) = self.info(&full_path) else {
+                continue;
+            };
+            pending.extend(info.children.iter().rev().cloned());
+            entries.push(info);
+        }
+        entries
+    }
+}
+
+fn entry_path(entry: &RegistryEntry) -> String {
+    match entry {
+        RegistryEntry::Task(path) | RegistryEntry::Group(path) => path.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::stack::BuildStack;
+    use rhai::Dynamic;
+
+    #[test]
+    fn entries_walk_groups_in_declaration_order_with_inherited_settings() {
+        let mut registry = TaskRegistry::new();
+        let mut stack = BuildStack::new();
+        stack.begin_group(&registry, "build").unwrap();
+        let mut env = rhai::Map::new();
+        env.insert("MODE".into(), "ci".into());
+        stack.set_env(env).unwrap();
+        stack.begin_task(&registry, "release").unwrap();
+        let mut profile = rhai::Map::new();
+        profile.insert("default".into(), Dynamic::from("debug"));
+        stack.add_arg("profile", profile).unwrap();
+        stack.end_task(&mut registry).unwrap();
+        stack.end_group(&mut registry).unwrap();
+        stack.begin_task(&registry, "lint").unwrap();
+        stack.end_task(&mut registry).unwrap();
+
+        let paths: Vec<String> = registry
+            .entries()
+            .into_iter()
+            .map(|info| info.full_path)
+            .collect();
+        assert_eq!(paths, ["build", "build.release", "lint"]);
+
+        let group = registry.info("build").expect("group info");
+        assert_eq!(group.kind, ListItemKind::Group);
+        assert_eq!(group.children, ["build.release"]);
+
+        let task = registry.info("build.release").expect("task info");
+        assert_eq!(task.name, "release");
+        assert_eq!(task.env.get("MODE").map(String::as_str), Some("ci"));
+        assert_eq!(task.params[0].name, "profile");
+        assert!(registry.info("missing").is_none());
+    }
+}
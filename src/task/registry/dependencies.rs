@@ -0,0 +1,155 @@
+use rhai::EvalAltResult;
+
+use super::resolver::TaskLookup;
+use super::task_registry::TaskRegistry;
+use crate::logger::trace;
+use crate::task::model::context_error;
+
+impl TaskRegistry {
+    pub(crate) fn validate_dependencies(&self) -> Result<(), Box<EvalAltResult>> {
+        for (full_path, _) in self.tasks_iter() {
+            self.dependency_order(full_path)?;
+        }
+        Ok(())
+    }
+
+    /// Dependencies of `full_path` in execution order, excluding the task itself.
+    pub(crate) fn dependency_order(
+        &self,
+        full_path: &str,
+    ) -> Result<Vec<String>, Box<EvalAltResult>> {
+        let mut order = Vec::new();
+        let mut active = vec![full_path.to_string()];
+        self.visit_dependencies(full_path, &mut active, &mut order)?;
+        trace!("dependency_order: '{}' -> {:?}", full_path, order);
+        Ok(order)
+    }
+
+    pub(crate) fn direct_dependencies(
+        &self,
+        full_path: &str,
+    ) -> Result<Vec<String>, Box<EvalAltResult>> {
+        self.task(full_path)
+            .map(|task| task.deps.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|identifier| self.resolve_dependency(full_path, identifier))
+            .collect()
+    }
+
+    fn resolve_dependency(
+        &self,
+        owner: &str,
+        identifier: &str,
+    ) -> Result<String, Box<EvalAltResult>> {
+        match self.resolve_task(identifier) {
+            TaskLookup::Found { full_path } => Ok(full_path),
+            TaskLookup::NotFound => Err(context_error(format!(
+                "Task '{}' depends on unknown task '{}'.",
+                owner, identifier
+            ))),
+            TaskLookup::Ambiguous(candidates) => {
+                let mut message = format!(
+                    "Task '{}' depends on '{}', which matches multiple candidates:\n",
+                    owner, identifier
+                );
+                for candidate in candidates {
+                    message.push_str(&format!("  - {}\n", candidate));
+                }
+                message.push_str("Please use the fully-qualified name (e.g. group.task).");
+                Err(context_error(message))
+            }
+        }
+    }
+
+    fn visit_dependencies(
+        &self,
+        full_path: &str,
+        active: &mut Vec<String>,
+        order: &mut Vec<String>,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let Some(task) = self.task(full_path) else {
+            return Ok(());
+        };
+
+        for identifier in &task.deps {
+            let dep = self.resolve_dependency(full_path, identifier)?;
+            if let Some(start) = active.iter().position(|path| *path == dep) {
+                let mut cycle: Vec<&str> = active[start..].iter().map(String::as_str).collect();
+                cycle.push(&dep);
+                return Err(context_error(format!(
+                    "Dependency cycle detected: {}",
+                    cycle.join(" -> ")
+                )));
+            }
+            if order.contains(&dep) {
+                continue;
+            }
+            active.push(dep.clone());
+            self.visit_dependencies(&dep, active, order)?;
+            active.pop();
+            order.push(dep);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::model::Task;
+
+    fn registry_with_deps(entries: &[(&str, &[&str])]) -> TaskRegistry {
+        let mut registry = TaskRegistry::new();
+        for (name, deps) in entries {
+            let task = Task {
+                deps: deps.iter().map(|dep| dep.to_string()).collect(),
+                ..Task::default()
+            };
+            registry.insert_task_entry(name.to_string(), task);
+        }
+        registry
+    }
+
+    #[test]
+    fn orders_shared_dependencies_once() {
+        let registry = registry_with_deps(&[
+            ("fmt", &[]),
+            ("lint", &["fmt"]),
+            ("test", &["fmt"]),
+            ("ci", &["lint", "test"]),
+        ]);
+        let order = registry.dependency_order("ci").expect("dependency order");
+        assert_eq!(order, vec!["fmt", "lint", "test"]);
+    }
+
+    #[test]
+    fn resolves_dependencies_by_leaf_name() {
+        let registry = registry_with_deps(&[("build.compile", &[]), ("release", &["compile"])]);
+        let order = registry
+            .dependency_order("release")
+            .expect("dependency order");
+        assert_eq!(order, vec!["build.compile"]);
+    }
+
+    #[test]
+    fn rejects_cycles_with_full_path() {
+        let registry = registry_with_deps(&[("a", &["b"]), ("b", &["c"]), ("c", &["a"])]);
+        let err = registry.validate_dependencies().unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Dependency cycle detected: a -> b -> c -> a"),
+            "unexpected error message: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_dependency() {
+        let registry = registry_with_deps(&[("build", &["missing"])]);
+        let err = registry.validate_dependencies().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Task 'build' depends on unknown task 'missing'."));
+    }
+}
@@ -0,0 +1,184 @@
+use rhai::Dynamic;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::freshness::matching_files;
+use crate::task::model::Task;
+
+/// Content hashes of everything a task run depends on: the text of its `task(...)`
+/// declaration, its resolved arguments, and its `sources()` files.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Fingerprint {
+    definition: String,
+    /// `None` when the arguments could not be resolved (e.g. for `rhask status`).
+    args: Option<String>,
+    /// `(path, sha256)` for every input file, sorted by path.
+    files: Vec<(String, String)>,
+}
+
+impl Fingerprint {
+    pub(crate) fn compute(task: &Task, args: Option<&[Dynamic]>) -> Result<Self, String> {
+        let mut files = Vec::new();
+        for pattern in &task.sources {
+            for (path, _) in matching_files(pattern)? {
+                let contents = fs::read(&path)
+                    .map_err(|err| format!("cannot read '{}': {}", path.display(), err))?;
+                files.push((
+                    path.to_string_lossy().into_owned(),
+                    hex_digest(Sha256::digest(&contents)),
+                ));
+            }
+        }
+        files.sort();
+        files.dedup();
+
+        let definition = hex_digest(Sha256::digest(
+            task.source_text.as_deref().unwrap_or_default(),
+        ));
+        let args = args.map(|args| {
+            let mut hasher = Sha256::new();
+            for arg in args {
+                hasher.update(format!("{}:{}\n", arg.type_name(), arg));
+            }
+            hex_digest(hasher.finalize())
+        });
+
+        Ok(Self {
+            definition,
+            args,
+            files,
+        })
+    }
+
+    pub(crate) fn load(path: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(path).ok()?;
+        let mut lines = contents.lines();
+        let definition = lines.next()?.strip_prefix("definition ")?.to_string();
+        let args = lines.next()?.strip_prefix("args ")?.to_string();
+        let mut files = Vec::new();
+        for line in lines {
+            let (hash, path) = line.strip_prefix("file ")?.split_once(' ')?;
+            files.push((path.to_string(), hash.to_string()));
+        }
+        Some(Self {
+            definition,
+            args: Some(args),
+            files,
+        })
+    }
+
+    pub(crate) fn store(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut contents = format!(
+            "definition {}\nargs {}\n",
+            self.definition,
+            self.args.as_deref().unwrap_or_default()
+        );
+        for (file, hash) in &self.files {
+            contents.push_str(&format!("file {} {}\n", hash, file));
+        }
+        fs::write(path, contents)
+    }
+
+    /// Whether nothing changed since `previous`; unknown arguments are not compared.
+    pub(crate) fn matches(&self, previous: &Self) -> bool {
+        self.definition == previous.definition
+            && self.files == previous.files
+            && (self.args.is_none() || self.args == previous.args)
+    }
+
+    /// Human-readable reason why this fingerprint does not match `previous`.
+    pub(crate) fn describe_change(&self, previous: &Self) -> String {
+        let changed = self.changed_files(previous);
+        if !changed.is_empty() {
+            format!("changed: {}", changed.join(", "))
+        } else if self.definition != previous.definition {
+            "task definition changed".to_string()
+        } else {
+            "arguments changed".to_string()
+        }
+    }
+
+    /// Input files that were added, removed, or modified since `previous`.
+    pub(crate) fn changed_files(&self, previous: &Self) -> Vec<String> {
+        let mut changed: Vec<String> = self
+            .files
+            .iter()
+            .filter(|entry| !previous.files.contains(entry))
+            .map(|(path, _)| path.clone())
+            .collect();
+        for (path, _) in &previous.files {
+            if !self.files.iter().any(|(current, _)| current == path) {
+                changed.push(path.clone());
+            }
+        }
+        changed.sort();
+        changed
+    }
+}
+
+/// Cache file holding the fingerprint of the last successful run of `full_path`.
+pub(crate) fn cache_path(cache_dir: &Path, full_path: &str) -> PathBuf {
+    cache_dir.join(full_path.replace(['/', '\\'], "_"))
+}
+
+fn hex_digest(bytes: impl AsRef<[u8]>) -> String {
+    bytes
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn task_reading(root: &Path) -> Task {
+        Task {
+            sources: vec![root.join("src/*.txt").to_string_lossy().into_owned()],
+            source_text: Some("task(\"gen\", || {})".to_string()),
+            ..Task::default()
+        }
+    }
+
+    #[test]
+    fn fingerprint_ignores_timestamps_but_tracks_content() {
+        let temp = tempdir().expect("temp dir");
+        fs::create_dir_all(temp.path().join("src")).expect("create src dir");
+        let input = temp.path().join("src/a.txt");
+        fs::write(&input, "one").expect("write input");
+        let task = task_reading(temp.path());
+
+        let first = Fingerprint::compute(&task, Some(&[])).expect("fingerprint");
+        fs::write(&input, "one").expect("rewrite input");
+        let same = Fingerprint::compute(&task, Some(&[])).expect("fingerprint");
+        assert!(same.matches(&first));
+
+        fs::write(&input, "two").expect("modify input");
+        let changed = Fingerprint::compute(&task, Some(&[])).expect("fingerprint");
+        assert!(!changed.matches(&first));
+        assert_eq!(
+            changed.changed_files(&first),
+            vec![input.to_string_lossy().into_owned()]
+        );
+    }
+
+    #[test]
+    fn fingerprint_tracks_arguments_and_round_trips() {
+        let temp = tempdir().expect("temp dir");
+        let task = task_reading(temp.path());
+        let debug = Fingerprint::compute(&task, Some(&["debug".into()])).expect("fingerprint");
+        let release = Fingerprint::compute(&task, Some(&["release".into()])).expect("fingerprint");
+        assert!(!debug.matches(&release));
+
+        let path = cache_path(&temp.path().join(".rhask/cache"), "build.gen");
+        debug.store(&path).expect("store fingerprint");
+        assert_eq!(Fingerprint::load(&path), Some(debug));
+    }
+}
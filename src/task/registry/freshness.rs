@@ -0,0 +1,315 @@
+use indexmap::IndexMap;
+use rhai::Dynamic;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::fingerprint::{cache_path, Fingerprint};
+use super::task_registry::TaskRegistry;
+use crate::logger::{trace, warn};
+use crate::task::arguments::prepare_arguments_from_parts;
+use crate::task::model::Task;
+
+/// Whether a task's actions need to run, as judged by `sources()`/`outputs()`
+/// timestamps or, with `fingerprint()`, by the cached content hashes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Freshness {
+    /// The task declares no `outputs()` or `fingerprint()`, so it always runs.
+    Untracked,
+    /// No outputs exist yet, or no successful run was recorded.
+    NeverRun,
+    UpToDate,
+    Stale {
+        reason: String,
+        /// Input files that changed since the last run.
+        changed: Vec<String>,
+    },
+}
+
+impl Freshness {
+    fn stale(reason: impl Into<String>) -> Self {
+        Self::Stale {
+            reason: reason.into(),
+            changed: Vec::new(),
+        }
+    }
+}
+
+/// Returned by [`TaskRegistry::plan_run`] when a task's actions must run.
+/// Call [`PendingRun::complete`] once they succeed so the fingerprint is recorded.
+#[must_use]
+pub(crate) struct PendingRun {
+    record: Option<(PathBuf, Fingerprint)>,
+}
+
+impl PendingRun {
+    pub(crate) fn complete(self) {
+        if let Some((path, fingerprint)) = self.record {
+            if let Err(err) = fingerprint.store(&path) {
+                warn!(
+                    "Failed to write fingerprint cache '{}': {}",
+                    path.display(),
+                    err
+                );
+            }
+        }
+    }
+}
+
+impl TaskRegistry {
+    /// Decides whether the actions of `full_path` should run, judged by its
+    /// `sources()`/`outputs()` timestamps or, with `fingerprint()`, by the content hash
+    /// recorded under `cache_dir`. Tasks that track their inputs print the decision and its
+    /// reason to stderr.
+    pub(crate) fn plan_run(
+        &self,
+        full_path: &str,
+        args: &[Dynamic],
+        force: bool,
+        cache_dir: Option<&Path>,
+    ) -> Option<PendingRun> {
+        let (freshness, record) = self.evaluate(full_path, Some(args), cache_dir);
+        trace!("plan_run: '{}' freshness={:?}", full_path, freshness);
+        let (run, message) = match freshness {
+            Freshness::Untracked => return Some(PendingRun { record }),
+            Freshness::UpToDate if force => (
+                true,
+                format!(
+                    "Task '{}' is up to date; running because of --force",
+                    full_path
+                ),
+            ),
+            Freshness::UpToDate => (
+                false,
+                format!("Task '{}' is up to date; skipping", full_path),
+            ),
+            Freshness::NeverRun => (true, format!("Task '{}' has never run; running", full_path)),
+            Freshness::Stale { reason, .. } => (
+                true,
+                format!("Task '{}' is out of date ({}); running", full_path, reason),
+            ),
+        };
+        crate::printer::warn(message);
+        run.then_some(PendingRun { record })
+    }
+
+    /// Freshness of `full_path` as `rhask run` without arguments would see it.
+    pub(crate) fn freshness(&self, full_path: &str, cache_dir: Option<&Path>) -> Freshness {
+        let args = prepare_arguments_from_parts(self, full_path, Vec::new(), IndexMap::new()).ok();
+        self.evaluate(full_path, args.as_deref(), cache_dir).0
+    }
+
+    fn evaluate(
+        &self,
+        full_path: &str,
+        args: Option<&[Dynamic]>,
+        cache_dir: Option<&Path>,
+    ) -> (Freshness, Option<(PathBuf, Fingerprint)>) {
+        let Some(task) = self.task(full_path) else {
+            return (Freshness::Untracked, None);
+        };
+        match (task.fingerprint, cache_dir) {
+            (true, Some(cache_dir)) => {
+                let path = cache_path(cache_dir, full_path);
+                match Fingerprint::compute(task, args) {
+                    Ok(current) => {
+                        let freshness = check_fingerprint(task, &current, &path);
+                        (freshness, Some((path, current)))
+                    }
+                    Err(reason) => (Freshness::stale(reason), None),
+                }
+            }
+            _ => (check_timestamps(task), None),
+        }
+    }
+}
+
+fn check_fingerprint(task: &Task, current: &Fingerprint, cache: &Path) -> Freshness {
+    let Some(previous) = Fingerprint::load(cache) else {
+        return Freshness::NeverRun;
+    };
+    if let Some(reason) = missing_output(task) {
+        return Freshness::stale(reason);
+    }
+    if current.matches(&previous) {
+        return Freshness::UpToDate;
+    }
+    Freshness::Stale {
+        reason: current.describe_change(&previous),
+        changed: current.changed_files(&previous),
+    }
+}
+
+fn missing_output(task: &Task) -> Option<String> {
+    for pattern in &task.outputs {
+        match matching_files(pattern) {
+            Ok(files) if files.is_empty() => {
+                return Some(format!("output '{}' does not exist", pattern));
+            }
+            Ok(_) => {}
+            Err(reason) => return Some(reason),
+        }
+    }
+    None
+}
+
+fn check_timestamps(task: &Task) -> Freshness {
+    if task.outputs.is_empty() {
+        return Freshness::Untracked;
+    }
+
+    let mut missing = Vec::new();
+    let mut oldest_output: Option<(PathBuf, SystemTime)> = None;
+    for pattern in &task.outputs {
+        let files = match matching_files(pattern) {
+            Ok(files) => files,
+            Err(reason) => return Freshness::stale(reason),
+        };
+        if files.is_empty() {
+            missing.push(pattern);
+        }
+        for (path, modified) in files {
+            if oldest_output
+                .as_ref()
+                .is_none_or(|(_, oldest)| modified < *oldest)
+            {
+                oldest_output = Some((path, modified));
+            }
+        }
+    }
+    let Some((output, output_time)) = oldest_output else {
+        return Freshness::NeverRun;
+    };
+    if let Some(pattern) = missing.first() {
+        return Freshness::stale(format!("output '{}' does not exist", pattern));
+    }
+
+    let mut changed = Vec::new();
+    for pattern in &task.sources {
+        let files = match matching_files(pattern) {
+            Ok(files) => files,
+            Err(reason) => return Freshness::stale(reason),
+        };
+        changed.extend(
+            files
+                .into_iter()
+                .filter(|(_, modified)| *modified >= output_time)
+                .map(|(path, _)| path.to_string_lossy().into_owned()),
+        );
+    }
+    if changed.is_empty() {
+        return Freshness::UpToDate;
+    }
+    changed.sort();
+    changed.dedup();
+    Freshness::Stale {
+        reason: format!("newer than '{}': {}", output.display(), changed.join(", ")),
+        changed,
+    }
+}
+
+/// Files matched by `pattern`; matched directories contribute every file beneath them,
+/// so `gen/**` covers the whole tree.
+pub(super) fn matching_files(pattern: &str) -> Result<Vec<(PathBuf, SystemTime)>, String> {
+    // `glob` only yields directories for a trailing `**`.
+    let expanded = if pattern.ends_with("**") {
+        format!("{}/*", pattern)
+    } else {
+        pattern.to_string()
+    };
+    let entries =
+        glob::glob(&expanded).map_err(|err| format!("invalid pattern '{}': {}", pattern, err))?;
+    let mut files = Vec::new();
+    for entry in entries {
+        match entry {
+            Ok(path) => collect_files(path, &mut files)?,
+            Err(err) => warn!("Failed to read '{}': {}", err.path().display(), err),
+        }
+    }
+    files.sort();
+    files.dedup_by(|a, b| a.0 == b.0);
+    Ok(files)
+}
+
+fn collect_files(path: PathBuf, files: &mut Vec<(PathBuf, SystemTime)>) -> Result<(), String> {
+    let inspect_error =
+        |path: &Path, err: io::Error| format!("cannot inspect '{}': {}", path.display(), err);
+    let metadata = fs::metadata(&path).map_err(|err| inspect_error(&path, err))?;
+    if metadata.is_dir() {
+        let entries = fs::read_dir(&path).map_err(|err| inspect_error(&path, err))?;
+        for entry in entries {
+            let entry = entry.map_err(|err| inspect_error(&path, err))?;
+            collect_files(entry.path(), files)?;
+        }
+    } else {
+        let modified = metadata
+            .modified()
+            .map_err(|err| inspect_error(&path, err))?;
+        files.push((path, modified));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    fn touch(path: &Path, age: Duration) {
+        fs::create_dir_all(path.parent().unwrap()).expect("create parent dir");
+        let file = File::create(path).expect("create file");
+        file.set_modified(SystemTime::now() - age)
+            .expect("set modified time");
+    }
+
+    fn task_with(root: &Path, sources: &[&str], outputs: &[&str]) -> Task {
+        let resolve = |pattern: &&str| root.join(pattern).to_string_lossy().into_owned();
+        Task {
+            sources: sources.iter().map(resolve).collect(),
+            outputs: outputs.iter().map(resolve).collect(),
+            ..Task::default()
+        }
+    }
+
+    #[test]
+    fn up_to_date_when_outputs_are_newer() {
+        let temp = tempdir().expect("temp dir");
+        touch(&temp.path().join("src/a.proto"), Duration::from_secs(60));
+        touch(&temp.path().join("gen/a.rs"), Duration::from_secs(10));
+        let task = task_with(temp.path(), &["src/**/*.proto"], &["gen/**"]);
+        assert_eq!(check_timestamps(&task), Freshness::UpToDate);
+    }
+
+    #[test]
+    fn stale_when_a_source_is_newer() {
+        let temp = tempdir().expect("temp dir");
+        touch(&temp.path().join("gen/a.rs"), Duration::from_secs(60));
+        touch(&temp.path().join("src/a.proto"), Duration::from_secs(10));
+        let task = task_with(temp.path(), &["src/**/*.proto"], &["gen/**"]);
+        match check_timestamps(&task) {
+            Freshness::Stale { changed, .. } => {
+                assert_eq!(changed.len(), 1);
+                assert!(changed[0].ends_with("a.proto"));
+            }
+            other => panic!("expected stale, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn never_run_when_outputs_are_missing() {
+        let temp = tempdir().expect("temp dir");
+        touch(&temp.path().join("src/a.proto"), Duration::from_secs(60));
+        let task = task_with(temp.path(), &["src/**/*.proto"], &["gen/**"]);
+        assert_eq!(check_timestamps(&task), Freshness::NeverRun);
+    }
+
+    #[test]
+    fn untracked_without_outputs() {
+        let temp = tempdir().expect("temp dir");
+        let task = task_with(temp.path(), &["src/**"], &[]);
+        assert_eq!(check_timestamps(&task), Freshness::Untracked);
+    }
+}
@@ -1,5 +1,10 @@
+mod dependencies;
+mod fingerprint;
+mod freshness;
 mod resolver;
 mod task_registry;
 
+pub(crate) use freshness::{Freshness, PendingRun};
 pub use resolver::TaskLookup;
+pub(crate) use task_registry::EffectiveSettings;
 pub use task_registry::TaskRegistry;
@@ -1,7 +1,8 @@
 use super::task_registry::TaskRegistry;
 use crate::logger::trace;
-use crate::task::model::leaf_name;
+use crate::task::model::is_path_suffix;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TaskLookup {
     Found { full_path: String },
     Ambiguous(Vec<String>),
@@ -23,33 +24,23 @@ impl TaskRegistry {
             };
         }
 
-        if trimmed.contains('.') {
-            if self.contains_task(trimmed) {
-                trace!("resolve_task: '{}' matched dotted task", trimmed);
-                return TaskLookup::Found {
-                    full_path: trimmed.to_string(),
-                };
-            } else {
-                trace!("resolve_task: dotted identifier '{}' not found", trimmed);
-                return TaskLookup::NotFound;
-            }
-        }
-
+        // A leaf name or a dotted identifier matches the tasks whose paths end with the
+        // same segments.
         let matches: Vec<String> = self
             .tasks_iter()
-            .filter(|(full_path, _)| leaf_name(full_path) == trimmed)
+            .filter(|(full_path, _)| is_path_suffix(full_path, trimmed))
             .map(|(full_path, _)| full_path.clone())
             .collect();
 
         match matches.len() {
             0 => {
-                trace!("resolve_task: '{}' not found as leaf", trimmed);
+                trace!("resolve_task: '{}' not found as suffix", trimmed);
                 TaskLookup::NotFound
             }
             1 => {
                 let full_path = matches.into_iter().next().unwrap();
                 trace!(
-                    "resolve_task: leaf '{}' resolved uniquely to '{}'",
+                    "resolve_task: suffix '{}' resolved uniquely to '{}'",
                     trimmed,
                     full_path
                 );
@@ -57,7 +48,7 @@ impl TaskRegistry {
             }
             _ => {
                 trace!(
-                    "resolve_task: leaf '{}' ambiguous matches {:?}",
+                    "resolve_task: suffix '{}' ambiguous matches {:?}",
                     trimmed,
                     matches
                 );
@@ -112,6 +103,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn resolves_dotted_suffix_by_segment() {
+        let registry = registry_with_tasks(&["build.release.deploy", "build.prerelease.deploy"]);
+        match registry.resolve_task("release.deploy") {
+            TaskLookup::Found { full_path } => assert_eq!(full_path, "build.release.deploy"),
+            other => panic!("unexpected lookup result: {:?}", result_desc(other)),
+        }
+        assert!(matches!(
+            registry.resolve_task("lease.deploy"),
+            TaskLookup::NotFound
+        ));
+    }
+
+    #[test]
+    fn detects_ambiguous_dotted_suffix() {
+        let registry = registry_with_tasks(&["build.release.deploy", "ops.release.deploy"]);
+        match registry.resolve_task("release.deploy") {
+            TaskLookup::Ambiguous(paths) => assert_eq!(
+                paths,
+                vec![
+                    "build.release.deploy".to_string(),
+                    "ops.release.deploy".to_string()
+                ]
+            ),
+            other => panic!("expected ambiguous, got {:?}", result_desc(other)),
+        }
+    }
+
     #[test]
     fn trims_identifier() {
         let registry = registry_with_tasks(&["ops.deploy"]);
@@ -1,8 +1,18 @@
 use indexmap::IndexMap;
 use rhai::EvalAltResult;
+use std::path::PathBuf;
 
 use crate::task::model::{context_error, Group, RegistryEntry, Task};
 
+/// Settings a task's actions run with once enclosing groups are taken into account.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct EffectiveSettings {
+    pub(crate) working_dir: Option<PathBuf>,
+    pub(crate) env: IndexMap<String, String>,
+    /// Words after `--`; filled in by the caller, never inherited.
+    pub(crate) passthrough: Vec<String>,
+}
+
 #[derive(Clone)]
 pub struct TaskRegistry {
     tasks: IndexMap<String, Task>,
@@ -67,6 +77,53 @@ impl TaskRegistry {
         self.root_entries.push(entry);
     }
 
+    /// Adds a task that was not declared by the script, creating any missing parent groups.
+    pub(crate) fn insert_native_task(
+        &mut self,
+        full_path: &str,
+        task: Task,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let full_path = full_path.trim();
+        if full_path
+            .split('.')
+            .any(|segment| segment.trim().is_empty())
+        {
+            return Err(context_error(format!("Invalid task name '{}'.", full_path)));
+        }
+        if self.contains_task(full_path) || self.contains_group(full_path) {
+            return Err(context_error(format!(
+                "Task '{}' is already defined.",
+                full_path
+            )));
+        }
+
+        let mut parent: Option<&str> = None;
+        for (index, _) in full_path.match_indices('.') {
+            let group_path = &full_path[..index];
+            if self.contains_task(group_path) {
+                return Err(context_error(format!(
+                    "Task '{}' cannot be placed inside task '{}'.",
+                    full_path, group_path
+                )));
+            }
+            if !self.contains_group(group_path) {
+                self.insert_group_entry(group_path.to_string(), Group::default());
+                self.attach_entry(parent, RegistryEntry::Group(group_path.to_string()));
+            }
+            parent = Some(group_path);
+        }
+        self.insert_task_entry(full_path.to_string(), task);
+        self.attach_entry(parent, RegistryEntry::Task(full_path.to_string()));
+        Ok(())
+    }
+
+    fn attach_entry(&mut self, parent: Option<&str>, entry: RegistryEntry) {
+        match parent.and_then(|path| self.groups.get_mut(path)) {
+            Some(group) => group.entries.push(entry),
+            None => self.root_entries.push(entry),
+        }
+    }
+
     pub(crate) fn set_default_task(&mut self, name: &str) -> Result<(), Box<EvalAltResult>> {
         let trimmed = name.trim();
         if trimmed.is_empty() {
@@ -84,6 +141,31 @@ impl TaskRegistry {
     pub(crate) fn default_task(&self) -> Option<String> {
         self.default_task.clone()
     }
+
+    /// Settings for `full_path`: each enclosing group's `dir()`/`env()` from the outermost
+    /// inwards, overridden by the task's own values.
+    pub(crate) fn effective_settings(&self, full_path: &str) -> EffectiveSettings {
+        let mut settings = EffectiveSettings::default();
+        for group in self.ancestor_groups(full_path) {
+            if group.working_dir.is_some() {
+                settings.working_dir = group.working_dir.clone();
+            }
+            settings.env.extend(group.env.clone());
+        }
+        if let Some(task) = self.task(full_path) {
+            if task.working_dir.is_some() {
+                settings.working_dir = task.working_dir.clone();
+            }
+            settings.env.extend(task.env.clone());
+        }
+        settings
+    }
+
+    fn ancestor_groups<'a>(&'a self, full_path: &'a str) -> impl Iterator<Item = &'a Group> {
+        full_path
+            .match_indices('.')
+            .filter_map(move |(index, _)| self.group(&full_path[..index]))
+    }
 }
 
 #[cfg(test)]
@@ -105,6 +187,31 @@ mod tests {
         assert!(registry.default_task().is_none());
     }
 
+    #[test]
+    fn effective_settings_let_tasks_override_group_env() {
+        let mut registry = TaskRegistry::new();
+        let mut outer = Group {
+            working_dir: Some(PathBuf::from("/work/web")),
+            ..Group::default()
+        };
+        outer.env.insert("MODE".into(), "outer".into());
+        outer.env.insert("OUTER".into(), "1".into());
+        let mut inner = Group::default();
+        inner.env.insert("MODE".into(), "inner".into());
+        registry.insert_group_entry("ops".into(), outer);
+        registry.insert_group_entry("ops.release".into(), inner);
+        let mut task = Task::default();
+        task.env.insert("TASK".into(), "1".into());
+        registry.insert_task_entry("ops.release.deploy".into(), task);
+
+        let settings = registry.effective_settings("ops.release.deploy");
+        assert_eq!(settings.working_dir, Some(PathBuf::from("/work/web")));
+        let env = settings.env;
+        assert_eq!(env.get("MODE").map(String::as_str), Some("inner"));
+        assert_eq!(env.get("OUTER").map(String::as_str), Some("1"));
+        assert_eq!(env.get("TASK").map(String::as_str), Some("1"));
+    }
+
     #[test]
     fn default_task_allows_single_definition() {
         let mut registry = TaskRegistry::new();
@@ -1,9 +1,13 @@
-use rhai::{EvalAltResult, FnPtr, ImmutableString, Map};
+use indexmap::IndexMap;
+use rhai::{Array, Dynamic, EvalAltResult, FnPtr, ImmutableString, Map, Position};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use super::declaration::call_text;
+use crate::task::arguments::{coerce_argument, ensure_choice};
 use crate::task::builder::{GroupBuilder, TaskBuilder};
-use crate::task::model::{context_error, ParameterSpec, RegistryEntry};
+use crate::task::model::{context_error, ParamType, ParameterSpec, RegistryEntry, SourceLocation};
 use crate::task::registry::TaskRegistry;
 
 #[derive(Clone, Debug)]
@@ -17,6 +21,9 @@ pub(crate) enum ContextFrame {
 pub struct BuildStack {
     context_stack: Vec<ContextFrame>,
     script_root: Option<PathBuf>,
+    source_files: HashMap<String, Option<String>>,
+    /// Text of a script compiled from memory, for declarations that have no source file.
+    inline_source: Option<String>,
 }
 
 impl Default for BuildStack {
@@ -24,6 +31,8 @@ impl Default for BuildStack {
         Self {
             context_stack: vec![ContextFrame::Root],
             script_root: None,
+            source_files: HashMap::new(),
+            inline_source: None,
         }
     }
 }
@@ -37,12 +46,18 @@ impl BuildStack {
         self.context_stack.clear();
         self.context_stack.push(ContextFrame::Root);
         self.script_root = None;
+        self.source_files.clear();
+        self.inline_source = None;
     }
 
     pub fn set_script_root(&mut self, root: PathBuf) {
         self.script_root = Some(root);
     }
 
+    pub fn set_inline_source(&mut self, source: String) {
+        self.inline_source = Some(source);
+    }
+
     pub(crate) fn begin_task(
         &mut self,
         registry: &TaskRegistry,
@@ -82,6 +97,41 @@ impl BuildStack {
         }
     }
 
+    /// Records where the current `task(...)` or `group(...)` call was declared. For tasks,
+    /// also captures the call's text from `source` (or the inline script) for fingerprinting.
+    pub(crate) fn record_declaration(&mut self, source: Option<&str>, position: Position) {
+        let (Some(line), Some(column)) = (position.line(), position.position()) else {
+            return;
+        };
+        let location = SourceLocation {
+            file: source.map(PathBuf::from),
+            line,
+            column,
+        };
+        match self.context_stack.last_mut() {
+            Some(ContextFrame::Task(builder)) => builder.set_declared_at(location),
+            Some(ContextFrame::Group(builder)) => {
+                builder.set_declared_at(location);
+                return;
+            }
+            _ => return,
+        }
+        let contents = match source {
+            Some(source) => self
+                .source_files
+                .entry(source.to_string())
+                .or_insert_with(|| fs::read_to_string(source).ok())
+                .as_deref(),
+            None => self.inline_source.as_deref(),
+        };
+        let text = contents.and_then(|contents| call_text(contents, line, column));
+        if let (Some(text), Some(ContextFrame::Task(builder))) =
+            (text, self.context_stack.last_mut())
+        {
+            builder.set_source_text(text.to_string());
+        }
+    }
+
     pub(crate) fn begin_group(
         &mut self,
         registry: &TaskRegistry,
@@ -150,34 +200,209 @@ impl BuildStack {
             }
         };
 
-        if builder.has_params() {
+        if builder.has_args_map() {
             return Err(context_error("args() can only be defined once per task()."));
         }
+        if builder.has_params() {
+            return Err(context_error(
+                "args() cannot be combined with arg(); declare every parameter with arg().",
+            ));
+        }
 
-        let mut entries: Vec<(String, Option<String>)> = params
+        let mut specs = params
             .into_iter()
-            .map(|(key, value)| {
-                let default = if value.is_unit() {
-                    None
-                } else if let Some(s) = value.clone().try_cast::<ImmutableString>() {
-                    Some(s.into())
-                } else {
-                    Some(value.to_string())
-                };
-                (key.into(), default)
-            })
-            .collect();
-
-        entries.sort_by(|a, b| a.0.cmp(&b.0));
+            .map(|(key, value)| parameter_spec(key.into(), value))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        specs.sort_by(|a, b| a.name.cmp(&b.name));
+        // The rest parameter always comes last, whatever its name.
+        specs.sort_by_key(|spec| spec.variadic);
+        ensure_unique_shorts(&specs)?;
+        if specs.iter().filter(|spec| spec.variadic).count() > 1 {
+            return Err(context_error("args(): only one parameter can be variadic."));
+        }
 
-        let specs = entries
-            .into_iter()
-            .map(|(name, default)| ParameterSpec { name, default })
-            .collect();
         builder.set_params(specs);
         Ok(())
     }
 
+    /// Declares one parameter; unlike `args()`, positionals follow declaration order.
+    pub fn add_arg(&mut self, name: &str, options: Map) -> Result<(), Box<EvalAltResult>> {
+        let builder = match self.context_stack.last_mut() {
+            Some(ContextFrame::Task(builder)) => builder,
+            Some(ContextFrame::Group(_)) | Some(ContextFrame::Root) => {
+                return Err(context_error("arg() can only be used inside task()."));
+            }
+            None => {
+                return Err(context_error("context mismatch: context stack is empty."));
+            }
+        };
+
+        if builder.has_args_map() {
+            return Err(context_error(
+                "arg() cannot be combined with args(); declare every parameter with arg().",
+            ));
+        }
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(context_error("arg() requires a non-empty name."));
+        }
+        if let Some(rest) = builder.params().iter().find(|param| param.variadic) {
+            return Err(context_error(format!(
+                "arg(): '{}' is variadic, so it must be declared last.",
+                rest.name
+            )));
+        }
+        if builder.params().iter().any(|param| param.name == name) {
+            return Err(context_error(format!(
+                "arg(): parameter '{}' is already declared.",
+                name
+            )));
+        }
+
+        let spec = parameter_from_options(name.to_string(), options)?;
+        let mut specs = builder.params().to_vec();
+        specs.push(spec.clone());
+        ensure_unique_shorts(&specs)?;
+        builder.add_param(spec);
+        Ok(())
+    }
+
+    pub fn set_env(&mut self, vars: Map) -> Result<(), Box<EvalAltResult>> {
+        let env = env_from_map(vars)?;
+        match self.context_stack.last_mut() {
+            Some(ContextFrame::Task(builder)) => {
+                if builder.has_env() {
+                    return Err(context_error("env() can only be defined once per task()."));
+                }
+                builder.set_env(env);
+                Ok(())
+            }
+            Some(ContextFrame::Group(builder)) => {
+                if builder.has_env() {
+                    return Err(context_error("env() can only be defined once per group()."));
+                }
+                builder.set_env(env);
+                Ok(())
+            }
+            Some(ContextFrame::Root) => Err(context_error(
+                "env() can only be used inside task() or group().",
+            )),
+            None => Err(context_error("context mismatch: context stack is empty.")),
+        }
+    }
+
+    pub fn set_deps(&mut self, deps: Array) -> Result<(), Box<EvalAltResult>> {
+        let builder = match self.context_stack.last_mut() {
+            Some(ContextFrame::Task(builder)) => builder,
+            Some(ContextFrame::Group(_)) | Some(ContextFrame::Root) => {
+                return Err(context_error("deps() can only be used inside task()."));
+            }
+            None => {
+                return Err(context_error("context mismatch: context stack is empty."));
+            }
+        };
+
+        if builder.has_deps() {
+            return Err(context_error("deps() can only be defined once per task()."));
+        }
+
+        let mut names: Vec<String> = Vec::with_capacity(deps.len());
+        for value in deps {
+            let name = value
+                .try_cast::<ImmutableString>()
+                .map(|s| s.trim().to_string())
+                .ok_or_else(|| context_error("deps() expects an array of task names."))?;
+            if name.is_empty() {
+                return Err(context_error("deps() task names cannot be empty."));
+            }
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+
+        builder.set_deps(names);
+        Ok(())
+    }
+
+    pub fn set_sources(&mut self, patterns: Array) -> Result<(), Box<EvalAltResult>> {
+        match self.context_stack.last() {
+            Some(ContextFrame::Task(builder)) => {
+                if builder.has_sources() {
+                    return Err(context_error(
+                        "sources() can only be defined once per task().",
+                    ));
+                }
+            }
+            Some(ContextFrame::Group(_)) | Some(ContextFrame::Root) => {
+                return Err(context_error("sources() can only be used inside task()."));
+            }
+            None => {
+                return Err(context_error("context mismatch: context stack is empty."));
+            }
+        }
+
+        let resolved = self.resolve_patterns("sources()", patterns)?;
+
+        match self.context_stack.last_mut() {
+            Some(ContextFrame::Task(builder)) => {
+                builder.set_sources(resolved);
+                Ok(())
+            }
+            _ => Err(context_error(
+                "sources() context mismatch while applying patterns.",
+            )),
+        }
+    }
+
+    pub fn set_outputs(&mut self, patterns: Array) -> Result<(), Box<EvalAltResult>> {
+        match self.context_stack.last() {
+            Some(ContextFrame::Task(builder)) => {
+                if builder.has_outputs() {
+                    return Err(context_error(
+                        "outputs() can only be defined once per task().",
+                    ));
+                }
+            }
+            Some(ContextFrame::Group(_)) | Some(ContextFrame::Root) => {
+                return Err(context_error("outputs() can only be used inside task()."));
+            }
+            None => {
+                return Err(context_error("context mismatch: context stack is empty."));
+            }
+        }
+
+        let resolved = self.resolve_patterns("outputs()", patterns)?;
+
+        match self.context_stack.last_mut() {
+            Some(ContextFrame::Task(builder)) => {
+                builder.set_outputs(resolved);
+                Ok(())
+            }
+            _ => Err(context_error(
+                "outputs() context mismatch while applying patterns.",
+            )),
+        }
+    }
+
+    pub fn enable_fingerprint(&mut self) -> Result<(), Box<EvalAltResult>> {
+        match self.context_stack.last_mut() {
+            Some(ContextFrame::Task(builder)) => {
+                if builder.has_fingerprint() {
+                    return Err(context_error(
+                        "fingerprint() can only be defined once per task().",
+                    ));
+                }
+                builder.enable_fingerprint();
+                Ok(())
+            }
+            Some(ContextFrame::Group(_)) | Some(ContextFrame::Root) => Err(context_error(
+                "fingerprint() can only be used inside task().",
+            )),
+            None => Err(context_error("context mismatch: context stack is empty.")),
+        }
+    }
+
     pub fn set_description(&mut self, desc: &str) -> Result<(), Box<EvalAltResult>> {
         match self.context_stack.last_mut() {
             Some(ContextFrame::Task(builder)) => {
@@ -207,8 +432,15 @@ impl BuildStack {
                     return Err(context_error("dir() can only be defined once per task()."));
                 }
             }
-            Some(ContextFrame::Group(_)) | Some(ContextFrame::Root) => {
-                return Err(context_error("dir() can only be used inside task()."));
+            Some(ContextFrame::Group(builder)) => {
+                if builder.has_working_dir() {
+                    return Err(context_error("dir() can only be defined once per group()."));
+                }
+            }
+            Some(ContextFrame::Root) => {
+                return Err(context_error(
+                    "dir() can only be used inside task() or group().",
+                ));
             }
             None => {
                 return Err(context_error("context mismatch: context stack is empty."));
@@ -221,6 +453,9 @@ impl BuildStack {
             Some(ContextFrame::Task(builder)) => {
                 builder.set_working_dir(resolved);
             }
+            Some(ContextFrame::Group(builder)) => {
+                builder.set_working_dir(resolved);
+            }
             _ => {
                 return Err(context_error(
                     "dir() context mismatch while applying working directory.",
@@ -267,25 +502,17 @@ impl BuildStack {
         registry: &TaskRegistry,
         full_path: &str,
     ) -> Result<(), Box<EvalAltResult>> {
-        if registry.contains_task(full_path)
-            || self
-                .context_stack
-                .iter()
-                .any(|frame| matches!(frame, ContextFrame::Task(builder) if builder.full_path == full_path))
-        {
+        if let Some(first) = self.existing_task(registry, full_path) {
             Err(context_error(format!(
-                "Task '{}' is already defined.",
-                full_path
+                "Task '{}' is already defined{}.",
+                full_path,
+                first_definition(first)
             )))
-        } else if registry.contains_group(full_path)
-            || self
-                .context_stack
-                .iter()
-                .any(|frame| matches!(frame, ContextFrame::Group(builder) if builder.full_path == full_path))
-        {
+        } else if let Some(first) = self.existing_group(registry, full_path) {
             Err(context_error(format!(
-                "Task '{}' is already defined as a group.",
-                full_path
+                "Task '{}' is already defined as a group{}.",
+                full_path,
+                first_definition(first)
             )))
         } else {
             Ok(())
@@ -297,31 +524,109 @@ impl BuildStack {
         registry: &TaskRegistry,
         full_path: &str,
     ) -> Result<(), Box<EvalAltResult>> {
-        if registry.contains_group(full_path)
-            || self
-                .context_stack
-                .iter()
-                .any(|frame| matches!(frame, ContextFrame::Group(builder) if builder.full_path == full_path))
-        {
+        if let Some(first) = self.existing_group(registry, full_path) {
             Err(context_error(format!(
-                "Group '{}' is already defined.",
-                full_path
+                "Group '{}' is already defined{}.",
+                full_path,
+                first_definition(first)
             )))
-        } else if registry.contains_task(full_path)
-            || self
-                .context_stack
-                .iter()
-                .any(|frame| matches!(frame, ContextFrame::Task(builder) if builder.full_path == full_path))
-        {
+        } else if let Some(first) = self.existing_task(registry, full_path) {
             Err(context_error(format!(
-                "Group '{}' is already defined as a task.",
-                full_path
+                "Group '{}' is already defined as a task{}.",
+                full_path,
+                first_definition(first)
             )))
         } else {
             Ok(())
         }
     }
 
+    /// `Some(declared_at)` when a task named `full_path` is registered or still being built.
+    fn existing_task<'a>(
+        &'a self,
+        registry: &'a TaskRegistry,
+        full_path: &str,
+    ) -> Option<Option<&'a SourceLocation>> {
+        if let Some(task) = registry.task(full_path) {
+            return Some(task.declared_at.as_ref());
+        }
+        self.context_stack.iter().find_map(|frame| match frame {
+            ContextFrame::Task(builder) if builder.full_path == full_path => {
+                Some(builder.declared_at())
+            }
+            _ => None,
+        })
+    }
+
+    /// `Some(declared_at)` when a group named `full_path` is registered or still being built.
+    fn existing_group<'a>(
+        &'a self,
+        registry: &'a TaskRegistry,
+        full_path: &str,
+    ) -> Option<Option<&'a SourceLocation>> {
+        if let Some(group) = registry.group(full_path) {
+            return Some(group.declared_at.as_ref());
+        }
+        self.context_stack.iter().find_map(|frame| match frame {
+            ContextFrame::Group(builder) if builder.full_path == full_path => {
+                Some(builder.declared_at())
+            }
+            _ => None,
+        })
+    }
+
+    /// Resolves glob patterns against the rhaskfile root, the same way `dir()` does.
+    fn resolve_patterns(
+        &self,
+        label: &str,
+        patterns: Array,
+    ) -> Result<Vec<String>, Box<EvalAltResult>> {
+        if patterns.is_empty() {
+            return Err(context_error(format!(
+                "{} requires at least one pattern.",
+                label
+            )));
+        }
+
+        let mut resolved: Vec<String> = Vec::with_capacity(patterns.len());
+        for value in patterns {
+            let pattern = value
+                .try_cast::<ImmutableString>()
+                .map(|s| s.trim().to_string())
+                .ok_or_else(|| {
+                    context_error(format!("{} expects an array of glob patterns.", label))
+                })?;
+            if pattern.is_empty() {
+                return Err(context_error(format!(
+                    "{} patterns cannot be empty.",
+                    label
+                )));
+            }
+
+            let raw = Path::new(&pattern);
+            let candidate = if raw.is_absolute() {
+                raw.to_path_buf()
+            } else {
+                let root = self.script_root.clone().ok_or_else(|| {
+                    context_error(format!(
+                        "{} cannot be used before the rhaskfile root is known.",
+                        label
+                    ))
+                })?;
+                Path::new(&glob::Pattern::escape(&root.to_string_lossy())).join(raw)
+            };
+            let candidate = candidate.to_string_lossy().into_owned();
+
+            glob::Pattern::new(&candidate).map_err(|err| {
+                context_error(format!("{}: invalid pattern '{}': {}", label, pattern, err))
+            })?;
+            if !resolved.contains(&candidate) {
+                resolved.push(candidate);
+            }
+        }
+        Ok(resolved)
+    }
+
     fn resolve_directory(&self, path: &str) -> Result<PathBuf, Box<EvalAltResult>> {
         let trimmed = path.trim();
         if trimmed.is_empty() {
@@ -364,6 +669,162 @@ impl BuildStack {
     }
 }
 
+/// Builds a parameter from an `args()` entry: `()` (required string), a default value whose
+/// type is inferred, or an options map as accepted by `arg()`.
+fn parameter_spec(name: String, value: Dynamic) -> Result<ParameterSpec, Box<EvalAltResult>> {
+    if value.is_unit() {
+        return Ok(ParameterSpec::new(name, ParamType::String, None));
+    }
+    if let Some(options) = value.clone().try_cast::<Map>() {
+        return parameter_from_options(name, options);
+    }
+    let ty = ParamType::infer(&value).ok_or_else(|| {
+        context_error(format!(
+            "args(): default for '{}' must be a string, int, float, bool, or array (got {}).",
+            name,
+            value.type_name()
+        ))
+    })?;
+    let default = coerce_argument(&name, &ty, value)?;
+    Ok(ParameterSpec::new(name, ty, Some(default)))
+}
+
+/// Parses `#{ type, default, help, choices, short }`. Without `type`, the type is inferred
+/// from `default`, then from the first choice, and falls back to string.
+fn parameter_from_options(name: String, options: Map) -> Result<ParameterSpec, Box<EvalAltResult>> {
+    let mut ty = None;
+    let mut default = None;
+    let mut help = None;
+    let mut choices = Vec::new();
+    let mut short = None;
+    let mut variadic = false;
+    for (key, option) in options {
+        match key.as_str() {
+            "type" => {
+                let type_name = option_string(&name, "type", option)?;
+                ty = Some(ParamType::from_name(&type_name).ok_or_else(|| {
+                    context_error(format!(
+                        "Parameter '{}': unknown type '{}' (expected string, int, float, bool, array or array<T>).",
+                        name, type_name
+                    ))
+                })?);
+            }
+            "default" if !option.is_unit() => default = Some(option),
+            "default" => {}
+            "help" => help = Some(option_string(&name, "help", option)?),
+            "choices" => {
+                choices = option.try_cast::<Array>().ok_or_else(|| {
+                    context_error(format!("Parameter '{}': choices must be an array.", name))
+                })?;
+            }
+            "variadic" => {
+                variadic = option.as_bool().map_err(|_| {
+                    context_error(format!("Parameter '{}': variadic must be a bool.", name))
+                })?;
+            }
+            "short" => {
+                let flag = option_string(&name, "short", option)?;
+                let mut chars = flag.trim_start_matches('-').chars();
+                short = match (chars.next(), chars.next()) {
+                    (Some(ch), None) if ch.is_ascii_alphabetic() => Some(ch),
+                    _ => {
+                        return Err(context_error(format!(
+                            "Parameter '{}': short must be a single letter, got '{}'.",
+                            name, flag
+                        )));
+                    }
+                };
+            }
+            other => {
+                return Err(context_error(format!(
+                    "Parameter '{}': unknown option '{}'.",
+                    name, other
+                )));
+            }
+        }
+    }
+
+    let mut ty = ty
+        .or_else(|| default.as_ref().and_then(ParamType::infer))
+        .or_else(|| choices.first().and_then(ParamType::infer))
+        .unwrap_or_default();
+    if variadic && !matches!(ty, ParamType::Array(_)) {
+        ty = ParamType::Array(Box::new(ty));
+    }
+    if variadic && default.is_none() {
+        default = Some(Dynamic::from_array(Array::new()));
+    }
+    let element = match &ty {
+        ParamType::Array(element) => element.as_ref(),
+        other => other,
+    };
+    let choices = choices
+        .into_iter()
+        .map(|choice| coerce_argument(&name, element, choice))
+        .collect::<Result<Vec<_>, _>>()?;
+    let default = default
+        .map(|value| coerce_argument(&name, &ty, value))
+        .transpose()?;
+    let mut spec = ParameterSpec::new(name, ty, default);
+    spec.help = help;
+    spec.choices = choices;
+    spec.short = short;
+    spec.variadic = variadic;
+    if let Some(default) = &spec.default {
+        ensure_choice(&spec, default)?;
+    }
+    Ok(spec)
+}
+
+fn option_string(name: &str, key: &str, value: Dynamic) -> Result<String, Box<EvalAltResult>> {
+    value
+        .into_immutable_string()
+        .map(Into::into)
+        .map_err(|_| context_error(format!("Parameter '{}': {} must be a string.", name, key)))
+}
+
+fn ensure_unique_shorts(specs: &[ParameterSpec]) -> Result<(), Box<EvalAltResult>> {
+    for (index, spec) in specs.iter().enumerate() {
+        if let Some(short) = spec.short {
+            if let Some(other) = specs[..index].iter().find(|p| p.short == Some(short)) {
+                return Err(context_error(format!(
+                    "Parameters '{}' and '{}' both use short flag '-{}'.",
+                    other.name, spec.name, short
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn env_from_map(vars: Map) -> Result<IndexMap<String, String>, Box<EvalAltResult>> {
+    if vars.is_empty() {
+        return Err(context_error("env() requires at least one variable."));
+    }
+    let mut env = IndexMap::with_capacity(vars.len());
+    for (key, value) in vars {
+        let value = if let Some(s) = value.clone().try_cast::<ImmutableString>() {
+            s.to_string()
+        } else if value.is_int() || value.is_float() || value.is_bool() || value.is_char() {
+            value.to_string()
+        } else {
+            return Err(context_error(format!(
+                "env(): value for '{}' must be a string, number or bool.",
+                key
+            )));
+        };
+        env.insert(key.to_string(), value);
+    }
+    Ok(env)
+}
+
+/// ` at <file:line:column>` pointing at the first definition, when it is known.
+fn first_definition(location: Option<&SourceLocation>) -> String {
+    location
+        .map(|location| format!(" at {}", location))
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -406,6 +867,82 @@ mod tests {
         assert!(stack.set_directory("scripts").is_err());
     }
 
+    #[test]
+    fn sources_resolve_relative_to_script_root() {
+        let temp = tempdir().expect("temp dir");
+        let root = temp.path().to_path_buf();
+
+        let mut stack = BuildStack::new();
+        stack.set_script_root(root.clone());
+        let mut registry = TaskRegistry::new();
+        stack.begin_task(&registry, "codegen").expect("begin task");
+        stack
+            .set_sources(vec!["proto/**/*.proto".into()])
+            .expect("set sources");
+        stack
+            .set_outputs(vec!["/abs/gen/**".into()])
+            .expect("set outputs");
+        assert!(stack.set_sources(vec!["other".into()]).is_err());
+        stack.end_task(&mut registry).expect("end task");
+
+        let task = registry.task("codegen").expect("task stored");
+        let expected = root.join("proto/**/*.proto").to_string_lossy().into_owned();
+        assert_eq!(task.sources, vec![expected]);
+        assert_eq!(task.outputs, vec!["/abs/gen/**".to_string()]);
+    }
+
+    #[test]
+    fn env_converts_scalars_and_rejects_root_scope() {
+        let mut stack = BuildStack::new();
+        let mut registry = TaskRegistry::new();
+        let vars = |key: &str, value: Dynamic| {
+            let mut map = Map::new();
+            map.insert(key.into(), value);
+            map
+        };
+        assert!(stack.set_env(vars("RUST_LOG", "debug".into())).is_err());
+
+        stack.begin_task(&registry, "serve").expect("begin task");
+        stack
+            .set_env(vars("PORT", 8080_i64.into()))
+            .expect("set env");
+        assert!(stack.set_env(vars("PORT", 9090_i64.into())).is_err());
+        stack.end_task(&mut registry).expect("end task");
+
+        let task = registry.task("serve").expect("task stored");
+        assert_eq!(task.env.get("PORT").map(String::as_str), Some("8080"));
+    }
+
+    #[test]
+    fn arg_keeps_declaration_order_and_validates_options() {
+        let mut stack = BuildStack::new();
+        let mut registry = TaskRegistry::new();
+        stack.begin_task(&registry, "deploy").expect("begin task");
+        let mut target = Map::new();
+        target.insert("help".into(), "where to deploy".into());
+        target.insert(
+            "choices".into(),
+            Dynamic::from_array(vec!["staging".into(), "prod".into()]),
+        );
+        target.insert("short".into(), "t".into());
+        stack.add_arg("target", target).expect("target arg");
+        stack.add_arg("app", Map::new()).expect("app arg");
+        assert!(stack.add_arg("app", Map::new()).is_err());
+        assert!(stack.set_args(Map::new()).is_err());
+
+        let mut bad_default = Map::new();
+        bad_default.insert("default".into(), "dev".into());
+        bad_default.insert("choices".into(), Dynamic::from_array(vec!["prod".into()]));
+        assert!(stack.add_arg("env", bad_default).is_err());
+        stack.end_task(&mut registry).expect("end task");
+
+        let task = registry.task("deploy").expect("task stored");
+        let names: Vec<&str> = task.params.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["target", "app"]);
+        assert_eq!(task.params[0].help.as_deref(), Some("where to deploy"));
+        assert_eq!(task.params[0].short, Some('t'));
+    }
+
     #[test]
     fn dir_requires_directory_to_exist() {
         let temp = tempdir().expect("temp dir");
@@ -0,0 +1,85 @@
+/// Source text of the call starting at `line`/`column` (1-based, as reported by Rhai),
+/// up to and including its closing parenthesis.
+pub(crate) fn call_text(contents: &str, line: usize, column: usize) -> Option<&str> {
+    let line_start = if line <= 1 {
+        0
+    } else {
+        contents
+            .match_indices('\n')
+            .nth(line - 2)
+            .map(|(index, _)| index + 1)?
+    };
+    let start = contents[line_start..]
+        .char_indices()
+        .nth(column.saturating_sub(1))
+        .map(|(offset, _)| line_start + offset)?;
+
+    let text = &contents[start..];
+    let mut chars = text.char_indices().peekable();
+    let mut depth = 0usize;
+    while let Some((index, ch)) = chars.next() {
+        match ch {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(&text[..index + ch.len_utf8()]);
+                }
+            }
+            '"' | '\'' | '`' => {
+                let quote = ch;
+                while let Some((_, next)) = chars.next() {
+                    if next == '\\' && quote != '`' {
+                        chars.next();
+                    } else if next == quote {
+                        break;
+                    }
+                }
+            }
+            '/' => match chars.peek() {
+                Some((_, '/')) => {
+                    for (_, next) in chars.by_ref() {
+                        if next == '\n' {
+                            break;
+                        }
+                    }
+                }
+                Some((_, '*')) => {
+                    chars.next();
+                    let mut previous = ' ';
+                    for (_, next) in chars.by_ref() {
+                        if previous == '*' && next == '/' {
+                            break;
+                        }
+                        previous = next;
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_whole_call_including_nested_blocks() {
+        let script = "// header\ntask(\"build\", || {\n    actions(|| { print(\")\"); });\n});\ntask(\"other\", || {});\n";
+        let text = call_text(script, 2, 1).expect("call text");
+        assert_eq!(
+            text,
+            "task(\"build\", || {\n    actions(|| { print(\")\"); });\n})"
+        );
+    }
+
+    #[test]
+    fn skips_brackets_inside_comments() {
+        let script = "  task(\"a\", || { /* ) */ // }\n });";
+        let text = call_text(script, 1, 3).expect("call text");
+        assert_eq!(text, "task(\"a\", || { /* ) */ // }\n })");
+    }
+}
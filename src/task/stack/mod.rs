@@ -1,3 +1,4 @@
 mod build_stack;
+mod declaration;
 
 pub use build_stack::BuildStack;
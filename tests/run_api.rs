@@ -1,4 +1,4 @@
-use rhask::cli::{Cli, Commands, ListOptions};
+use rhask::cli::{Cli, Commands, ListFormat, ListOptions};
 use rhask::run_with_cli;
 
 fn fixture_rhaskfile() -> String {
@@ -10,9 +10,13 @@ fn fixture_rhaskfile() -> String {
 fn run_with_cli_lists_tasks_successfully() {
     let cli = Cli {
         file: Some(fixture_rhaskfile()),
+        jobs: 1,
+        max_trigger_depth: rhask::engine::DEFAULT_MAX_TRIGGER_DEPTH,
         cmd: Some(Commands::List(ListOptions {
             group: None,
             flat: false,
+            settings: false,
+            format: ListFormat::Text,
         })),
     };
 
@@ -23,6 +27,8 @@ fn run_with_cli_lists_tasks_successfully() {
 fn run_with_cli_propagates_errors() {
     let cli = Cli {
         file: Some(fixture_rhaskfile()),
+        jobs: 1,
+        max_trigger_depth: rhask::engine::DEFAULT_MAX_TRIGGER_DEPTH,
         cmd: Some(Commands::Direct(Vec::new())),
     };
 
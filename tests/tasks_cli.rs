@@ -101,6 +101,92 @@ fn list_ambiguous_group() {
         .stderr(contains("matches multiple candidates"));
 }
 
+#[test]
+fn dotted_suffixes_resolve_tasks_groups_and_triggers() {
+    let temp = tempdir().expect("create temp dir");
+    let script_path = temp.path().join("rhaskfile.rhai");
+    fs::write(
+        &script_path,
+        r#"
+group("build", || {
+    group("release", || {
+        task("deploy", || { actions(|| {}); });
+    });
+});
+group("ops", || {
+    group("release", || {
+        task("deploy", || { actions(|| {}); });
+    });
+    group("nightly", || {
+        task("deploy", || { actions(|| { print("nightly deployed"); }); });
+    });
+});
+task("ship", || { actions(|| { trigger("nightly.deploy"); }); });
+"#,
+    )
+    .expect("write script");
+    let script = script_path.to_str().unwrap();
+
+    rhask()
+        .args(["--file", script, "run", "nightly.deploy"])
+        .assert()
+        .success()
+        .stdout(contains("nightly deployed"));
+    rhask()
+        .args(["--file", script, "run", "ship"])
+        .assert()
+        .success()
+        .stdout(contains("nightly deployed"));
+    rhask()
+        .args(["--file", script, "run", "release.deploy"])
+        .assert()
+        .failure()
+        .code(67)
+        .stderr(contains("build.release.deploy").and(contains("ops.release.deploy")));
+    rhask()
+        .args(["--file", script, "run", "s.nightly.deploy"])
+        .assert()
+        .failure()
+        .code(66);
+    rhask()
+        .args(["--file", script, "list", "--flat", "ops.nightly"])
+        .assert()
+        .success()
+        .stdout(contains("ops.nightly.deploy"));
+}
+
+#[test]
+fn list_format_json_reports_messages_in_the_document() {
+    rhask_with_fixture()
+        .args(["list", "unknown_group", "--format", "json"])
+        .assert()
+        .success()
+        .stdout(contains(r#""level": "warn""#))
+        .stdout(contains("Group 'unknown_group' does not exist."))
+        .stderr(contains("does not exist").not());
+
+    let output = rhask_with_fixture()
+        .args(["list", "--format", "json"])
+        .output()
+        .expect("run rhask");
+    let document: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout is valid JSON");
+    assert_eq!(document["default_task"], "clean");
+}
+
+#[test]
+fn list_format_tsv_prints_one_row_per_entry() {
+    rhask_with_fixture()
+        .args(["list", "--format", "tsv"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "kind\tfull_name\tdescription\tparams\tdir\tdefault\n",
+        ))
+        .stdout(contains("task\tbuild_suite.build_debug\t"))
+        .stdout(contains("task\tclean\t").and(contains("\tyes\n")));
+}
+
 #[test]
 fn run_unique_task_by_leaf_name() {
     rhask_with_fixture()
@@ -147,6 +233,75 @@ fn run_requires_full_path_when_ambiguous() {
         ));
 }
 
+#[test]
+fn show_prints_task_details() {
+    rhask_with_fixture()
+        .args(["show", "build"])
+        .assert()
+        .success()
+        .stdout(is_match(r"build\s+Build with profile and target").expect("regex compile"))
+        .stdout(contains("declared at: ").and(contains("rhaskfile.rhai:19")))
+        .stdout(contains(r#"1. profile (string), default: "debug""#))
+        .stdout(contains(
+            r#"2. target (string), default: "x86_64-unknown-linux-gnu""#,
+        ));
+}
+
+#[test]
+fn show_requires_full_path_when_ambiguous() {
+    rhask_with_fixture()
+        .args(["show", "deploy_staging"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "error: Task 'deploy_staging' matches multiple candidates:",
+        ));
+}
+
+#[test]
+fn declaration_positions_cover_imports_and_redefinitions() {
+    let temp = tempdir().expect("create temp dir");
+    fs::create_dir_all(temp.path().join("lib")).expect("create lib dir");
+    fs::write(
+        temp.path().join("lib/tools.rhai"),
+        "\ngroup(\"tools\", || {\n    task(\"fmt\", || { actions(|| {}); });\n});\n",
+    )
+    .expect("write module");
+    let script_path = temp.path().join("rhaskfile.rhai");
+    fs::write(
+        &script_path,
+        "import \"lib/tools\" as tools;\n\ntask(\"build\", || { actions(|| {}); });\n",
+    )
+    .expect("write script");
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["list", "--settings"])
+        .assert()
+        .success()
+        .stdout(
+            is_match(r"> tools\s*\n\s+declared at: lib/tools:2:1\n")
+                .unwrap()
+                .and(is_match(r"- fmt\s*\n\s+declared at: lib/tools:3:5\n").unwrap())
+                .and(contains("declared at: rhaskfile.rhai:3:1")),
+        );
+
+    let mut file = fs::OpenOptions::new()
+        .append(true)
+        .open(&script_path)
+        .expect("open script");
+    writeln!(file, "task(\"build\", || {{}});").expect("append task");
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["list"])
+        .assert()
+        .failure()
+        .stderr(
+            contains("Task 'build' is already defined at ").and(contains("rhaskfile.rhai:3:1.")),
+        );
+}
+
 #[test]
 fn run_with_full_path_executes_task() {
     rhask_with_fixture()
@@ -201,6 +356,52 @@ fn run_task_missing_required_arg_fails() {
         .stderr(contains("Argument 'version' is missing"));
 }
 
+#[test]
+fn typed_args_are_parsed_before_reaching_actions() {
+    let temp = tempdir().expect("create temp dir");
+    let script_path = temp.path().join("rhaskfile.rhai");
+    let mut file = fs::File::create(&script_path).expect("create script file");
+    writeln!(
+        file,
+        r#"
+            task("deploy", || {{
+                args(#{{ retries: 3, verbose: false, ports: [80], ratio: #{{ type: "float" }} }});
+                actions(|ports, ratio, retries, verbose| {{
+                    print(`retries=${{retries + 1}} verbose=${{!verbose}} ports=${{ports.len()}} ratio=${{type_of(ratio)}}`);
+                }});
+            }});
+
+            task("caller", || {{
+                actions(|| {{
+                    trigger("deploy", #{{ ratio: 1, retries: 9 }});
+                }});
+            }});
+        "#
+    )
+    .expect("write script");
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["run", "deploy", "--ports=80,443", "0.5", "--retries", "4"])
+        .assert()
+        .success()
+        .stdout(contains("retries=5 verbose=true ports=2 ratio=f64"));
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["run", "caller"])
+        .assert()
+        .success()
+        .stdout(contains("retries=10 verbose=true ports=1 ratio=f64"));
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["run", "deploy", "--ratio=1", "--retries=abc"])
+        .assert()
+        .failure()
+        .stderr(contains("Argument 'retries' expects an integer, got 'abc'"));
+}
+
 #[test]
 fn run_task_required_arg_success() {
     rhask_with_fixture()
@@ -459,6 +660,153 @@ fn dir_settings_do_not_leak_between_triggered_tasks() {
     assert_eq!(child_contents.trim(), expected_child);
 }
 
+#[test]
+fn env_applies_to_processes_and_follows_callee_wins_rule() {
+    let temp = tempdir().expect("create temp dir");
+    let root = temp.path();
+    let script_path = root.join("rhaskfile.rhai");
+    let mut file = fs::File::create(&script_path).expect("create script file");
+    writeln!(
+        file,
+        r#"
+            group("web", || {{
+                env(#{{ MODE: "group", PORT: 8080 }});
+                task("serve", || {{
+                    env(#{{ MODE: "task" }});
+                    actions(|| {{
+                        exec(cmd(["sh", "-c", "echo serve=$MODE:$PORT"]).build());
+                        exec(cmd(["sh", "-c", "echo override=$MODE"]).env(#{{ MODE: "inline" }}).build());
+                    }});
+                }});
+            }});
+
+            task("plain", || {{
+                actions(|| {{
+                    exec(cmd(["sh", "-c", "echo plain=${{MODE:-unset}}"]).build());
+                }});
+            }});
+
+            task("parent", || {{
+                env(#{{ MODE: "parent" }});
+                actions(|| {{
+                    trigger("web.serve");
+                    trigger("plain");
+                    exec(cmd(["sh", "-c", "echo parent=$MODE"]).build());
+                }});
+            }});
+        "#
+    )
+    .expect("write script");
+
+    rhask()
+        .current_dir(root)
+        .args(["run", "parent"])
+        .assert()
+        .success()
+        .stdout(
+            contains("serve=task:8080")
+                .and(contains("override=inline"))
+                .and(contains("plain=unset"))
+                .and(contains("parent=parent")),
+        );
+}
+
+#[test]
+fn env_of_the_running_task_applies_to_builders_passed_through_trigger() {
+    let temp = tempdir().expect("create temp dir");
+    let root = temp.path();
+    let script_path = root.join("rhaskfile.rhai");
+    let mut file = fs::File::create(&script_path).expect("create script file");
+    writeln!(
+        file,
+        r#"
+            let handoff = ();
+
+            task("caller", || {{
+                env(#{{ MODE: "caller", LEVEL: "caller" }});
+                actions(|| {{
+                    handoff = cmd(["sh", "-c", "echo mode=$MODE level=$LEVEL"])
+                        .env(#{{ LEVEL: "inline" }})
+                        .build();
+                    trigger("callee");
+                }});
+            }});
+
+            task("callee", || {{
+                env(#{{ MODE: "callee", LEVEL: "callee" }});
+                actions(|| {{
+                    exec(handoff);
+                }});
+            }});
+        "#
+    )
+    .expect("write script");
+
+    rhask()
+        .current_dir(root)
+        .args(["run", "caller"])
+        .assert()
+        .success()
+        .stdout(contains("mode=callee level=inline"));
+}
+
+#[test]
+fn group_dir_and_env_are_inherited_unless_task_overrides() {
+    let temp = tempdir().expect("create temp dir");
+    let root = temp.path();
+    fs::create_dir_all(root.join("web/admin")).expect("create web dirs");
+    let script_path = root.join("rhaskfile.rhai");
+    let mut file = fs::File::create(&script_path).expect("create script file");
+    writeln!(
+        file,
+        r#"
+            group("frontend", || {{
+                dir("web");
+                env(#{{ NODE_ENV: "production" }});
+                task("build", || {{
+                    actions(|| {{
+                        exec(cmd(["sh", "-c", "pwd > build_cwd.txt"]).build());
+                    }});
+                }});
+                task("admin", || {{
+                    dir("web/admin");
+                    actions(|| {{
+                        exec(cmd(["sh", "-c", "pwd > admin_cwd.txt"]).build());
+                    }});
+                }});
+            }});
+        "#
+    )
+    .expect("write script");
+
+    for task in ["frontend.build", "frontend.admin"] {
+        rhask()
+            .current_dir(root)
+            .args(["run", task])
+            .assert()
+            .success();
+    }
+    let web = root.join("web").canonicalize().expect("canonical web dir");
+    let build_cwd = fs::read_to_string(web.join("build_cwd.txt")).expect("read build cwd");
+    let admin_cwd = fs::read_to_string(web.join("admin/admin_cwd.txt")).expect("read admin cwd");
+    assert_eq!(build_cwd.trim(), web.to_str().expect("utf8 path"));
+    assert_eq!(
+        admin_cwd.trim(),
+        web.join("admin").to_str().expect("utf8 path")
+    );
+
+    rhask()
+        .current_dir(root)
+        .args(["list", "--settings"])
+        .assert()
+        .success()
+        .stdout(
+            is_match(r"- build\s*\n\s+dir: web\n\s+env: NODE_ENV=production")
+                .unwrap()
+                .and(is_match(r"- admin\s*\n\s+dir: web/admin\n").unwrap()),
+        );
+}
+
 #[test]
 fn child_without_dir_runs_in_launcher_directory() {
     let temp = tempdir().expect("create temp dir");
@@ -649,13 +997,316 @@ fn complete_tasks_lists_candidates() {
         .stdout(contains("build_suite").and(contains("build_suite.release_flow")));
 }
 
+#[test]
+fn boolean_params_accept_valueless_switches() {
+    let temp = tempdir().expect("create temp dir");
+    let script_path = temp.path().join("rhaskfile.rhai");
+    let mut file = fs::File::create(&script_path).expect("create script file");
+    writeln!(
+        file,
+        r#"
+            task("deploy", || {{
+                arg("target");
+                arg("verbose", #{{ "default": false, short: "v" }});
+                arg("cache", #{{ "default": true }});
+                actions(|target, verbose, cache| {{
+                    print(`target=${{target}} verbose=${{verbose}} cache=${{cache}}`);
+                }});
+            }});
+        "#
+    )
+    .expect("write script");
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["deploy", "--verbose", "prod", "--no-cache"])
+        .assert()
+        .success()
+        .stdout(contains("target=prod verbose=true cache=false"));
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["deploy", "-v", "staging"])
+        .assert()
+        .success()
+        .stdout(contains("target=staging verbose=true cache=true"));
+}
+
+#[test]
+fn failing_exec_reports_trigger_chain_and_command() {
+    let temp = tempdir().expect("create temp dir");
+    let script_path = temp.path().join("rhaskfile.rhai");
+    let mut file = fs::File::create(&script_path).expect("create script file");
+    writeln!(
+        file,
+        r#"
+            group("build", || {{
+                task("release", || {{
+                    actions(|| {{
+                        exec(cmd(["sh", "-c", "exit 3"]).build());
+                    }});
+                }});
+                task("tolerant", || {{
+                    actions(|| {{
+                        let result = exec(cmd(["sh", "-c", "exit 3"]).build().allow_exit_codes([3]));
+                        print(`status=${{result.status}}`);
+                    }});
+                }});
+            }});
+            task("release", || {{
+                actions(|| {{
+                    trigger("build.release");
+                }});
+            }});
+        "#
+    )
+    .expect("write script");
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["release"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            r#"release -> build.release: `sh -c "exit 3"` exited with status 3"#,
+        ));
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["build.tolerant"])
+        .assert()
+        .success()
+        .stdout(contains("status=3"));
+}
+
+#[test]
+fn exit_status_tells_failures_apart() {
+    let temp = tempdir().expect("create temp dir");
+    let script_path = temp.path().join("rhaskfile.rhai");
+    fs::write(
+        &script_path,
+        r#"
+            group("unit", || {
+                task("test", || {
+                    actions(|| {
+                        exec(cmd(["sh", "-c", "exit 101"]).build());
+                    });
+                });
+            });
+            group("docs", || {
+                task("test", || {
+                    args(#{ level: #{ type: "int" } });
+                    actions(|level| {
+                        throw "docs failed";
+                    });
+                });
+            });
+            task("ci", || {
+                actions(|| {
+                    trigger("unit.test");
+                });
+            });
+        "#,
+    )
+    .expect("write script");
+
+    let run = |args: &[&str]| {
+        rhask()
+            .current_dir(temp.path())
+            .args(args)
+            .assert()
+            .failure()
+    };
+    run(&["ci"]).code(101).stderr(contains(
+        "ci -> unit.test: `sh -c \"exit 101\"` exited with status 101",
+    ));
+    run(&["missing"]).code(66);
+    run(&["test"]).code(67);
+    run(&["docs.test", "--level=high"]).code(64);
+    run(&["docs.test", "--level=1"])
+        .code(1)
+        .stderr(contains("docs failed"));
+
+    fs::write(&script_path, "task(\"broken\", || {").expect("write script");
+    run(&["broken"]).code(65);
+}
+
+#[test]
+fn variadic_param_collects_remaining_positionals() {
+    let temp = tempdir().expect("create temp dir");
+    let script_path = temp.path().join("rhaskfile.rhai");
+    let mut file = fs::File::create(&script_path).expect("create script file");
+    writeln!(
+        file,
+        r#"
+            task("test", || {{
+                args(#{{ filters: #{{ variadic: true }}, jobs: 1 }});
+                actions(|jobs, filters| {{
+                    print(`jobs=${{jobs}} filters=${{filters.len()}}:${{filters}}`);
+                }});
+            }});
+        "#
+    )
+    .expect("write script");
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["test", "4", "parser", "lexer"])
+        .assert()
+        .success()
+        .stdout(contains(r#"jobs=4 filters=2:["parser", "lexer"]"#));
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["test"])
+        .assert()
+        .success()
+        .stdout(contains("jobs=1 filters=0:[]"));
+}
+
+#[test]
+fn repeated_array_options_accumulate() {
+    let temp = tempdir().expect("create temp dir");
+    let script_path = temp.path().join("rhaskfile.rhai");
+    let mut file = fs::File::create(&script_path).expect("create script file");
+    writeln!(
+        file,
+        r#"
+            task("build", || {{
+                args(#{{ features: [], profile: "debug" }});
+                actions(|features, profile| {{
+                    print(`features=${{features}} profile=${{profile}}`);
+                }});
+            }});
+        "#
+    )
+    .expect("write script");
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["build", "--features", "std", "--features=serde,log"])
+        .assert()
+        .success()
+        .stdout(contains(
+            r#"features=["std", "serde", "log"] profile=debug"#,
+        ));
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["build", "--profile=release", "--profile", "debug"])
+        .assert()
+        .failure()
+        .stderr(contains("Argument 'profile' was given more than once."));
+}
+
+#[test]
+fn passthrough_arguments_reach_actions_verbatim() {
+    let temp = tempdir().expect("create temp dir");
+    let script_path = temp.path().join("rhaskfile.rhai");
+    let mut file = fs::File::create(&script_path).expect("create script file");
+    writeln!(
+        file,
+        r#"
+            task("test", || {{
+                args(#{{ mode: "debug" }});
+                actions(|mode| {{
+                    print(`mode=${{mode}} extra=${{passthrough()}}`);
+                }});
+            }});
+            task("ci", || {{
+                actions(|| {{
+                    trigger("test", ["release", "--", "--nocapture"]);
+                }});
+            }});
+        "#
+    )
+    .expect("write script");
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["run", "test", "--", "--mode=release", "-q"])
+        .assert()
+        .success()
+        .stdout(contains(r#"mode=debug extra=["--mode=release", "-q"]"#));
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["test", "--mode=release", "--", "--nocapture"])
+        .assert()
+        .success()
+        .stdout(contains(r#"mode=release extra=["--nocapture"]"#));
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["ci"])
+        .assert()
+        .success()
+        .stdout(contains(r#"mode=release extra=["--nocapture"]"#));
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["test"])
+        .assert()
+        .success()
+        .stdout(contains("mode=debug extra=[]"));
+}
+
+#[test]
+fn complete_tasks_lists_arguments_with_help() {
+    let temp = tempdir().expect("create temp dir");
+    let script_path = temp.path().join("rhaskfile.rhai");
+    let mut file = fs::File::create(&script_path).expect("create script file");
+    writeln!(
+        file,
+        r#"
+            task("deploy", || {{
+                arg("target", #{{ help: "where to deploy", choices: ["staging", "prod"] }});
+                arg("app", #{{ "default": "web" }});
+                actions(|target, app| {{
+                    print(`deploy ${{app}} to ${{target}}`);
+                }});
+            }});
+        "#
+    )
+    .expect("write script");
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["complete-tasks", "--args", "deploy"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "--target\twhere to deploy\n--target=staging\n--target=prod\n--app\n",
+        ));
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["deploy", "prod", "api"])
+        .assert()
+        .success()
+        .stdout(contains("deploy api to prod"));
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["deploy", "dev"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Argument 'target' must be one of: staging, prod (got 'dev')",
+        ));
+}
+
 #[test]
 fn generate_completions_outputs_script() {
     rhask()
         .args(["completions", "bash"])
         .assert()
         .success()
-        .stdout(contains("complete -F").and(contains("rhask")));
+        .stdout(
+            contains("complete -F")
+                .and(contains("rhask"))
+                .and(contains("complete-tasks --args")),
+        );
 }
 
 #[test]
@@ -697,3 +1348,351 @@ fn dir_defined_twice_in_same_task_fails_on_load() {
         .failure()
         .stderr(contains("dir() can only be defined once per task()."));
 }
+
+#[test]
+fn deps_run_shared_prerequisites_once_in_order() {
+    let temp = tempdir().expect("create temp dir");
+    let script_path = temp.path().join("rhaskfile.rhai");
+    let mut file = fs::File::create(&script_path).expect("create script file");
+    writeln!(
+        file,
+        r#"
+            task("fmt", || {{
+                actions(|| {{ print("[fmt] ran"); }});
+            }});
+            task("lint", || {{
+                deps(["fmt"]);
+                actions(|| {{ print("[lint] ran"); }});
+            }});
+            task("test", || {{
+                deps(["fmt"]);
+                actions(|| {{ print("[test] ran"); }});
+            }});
+            task("ci", || {{
+                deps(["lint", "test"]);
+                actions(|| {{ print("[ci] ran"); }});
+            }});
+        "#
+    )
+    .expect("write script");
+
+    let output = rhask()
+        .args(["--file", script_path.to_str().expect("utf8 path"), "ci"])
+        .output()
+        .expect("run rhask");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("utf8 stdout");
+    let lines: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.ends_with("ran"))
+        .collect();
+    assert_eq!(
+        lines,
+        vec!["[fmt] ran", "[lint] ran", "[test] ran", "[ci] ran"]
+    );
+}
+
+#[test]
+fn deps_cycle_fails_on_load() {
+    let temp = tempdir().expect("create temp dir");
+    let script_path = temp.path().join("rhaskfile.rhai");
+    let mut file = fs::File::create(&script_path).expect("create script file");
+    writeln!(
+        file,
+        r#"
+            task("a", || {{ deps(["b"]); actions(|| {{}}); }});
+            task("b", || {{ deps(["c"]); actions(|| {{}}); }});
+            task("c", || {{ deps(["a"]); actions(|| {{}}); }});
+        "#
+    )
+    .expect("write script");
+
+    rhask()
+        .args(["--file", script_path.to_str().expect("utf8 path"), "list"])
+        .assert()
+        .failure()
+        .stderr(contains("Dependency cycle detected: a -> b -> c -> a"));
+}
+
+#[test]
+fn max_trigger_depth_flag_limits_nested_triggers() {
+    let temp = tempdir().expect("create temp dir");
+    let script_path = temp.path().join("rhaskfile.rhai");
+    let mut file = fs::File::create(&script_path).expect("create script file");
+    writeln!(
+        file,
+        r#"
+            task("release", || {{ actions(|| {{ trigger("build"); }}); }});
+            task("build", || {{ actions(|| {{ trigger("compile"); }}); }});
+            task("compile", || {{ actions(|| {{ print("[compile] ran"); }}); }});
+        "#
+    )
+    .expect("write script");
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["--max-trigger-depth", "2", "release"])
+        .assert()
+        .success()
+        .stdout(contains("[compile] ran"));
+
+    rhask()
+        .current_dir(temp.path())
+        .args(["--max-trigger-depth", "1", "release"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "trigger depth limit of 1 exceeded: release -> build -> compile",
+        ));
+}
+
+#[test]
+fn jobs_runs_dependencies_and_prints_summary() {
+    let temp = tempdir().expect("create temp dir");
+    let script_path = temp.path().join("rhaskfile.rhai");
+    let mut file = fs::File::create(&script_path).expect("create script file");
+    writeln!(
+        file,
+        r#"
+            task("left", || {{
+                actions(|| {{ print("[left] ran"); }});
+            }});
+            task("right", || {{
+                actions(|| {{ print("[right] ran"); }});
+            }});
+            task("join", || {{
+                deps(["left", "right"]);
+                actions(|| {{ print("[join] ran"); }});
+            }});
+        "#
+    )
+    .expect("write script");
+
+    rhask()
+        .args([
+            "--file",
+            script_path.to_str().expect("utf8 path"),
+            "-j",
+            "2",
+            "join",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("[left] ran").and(contains("[right] ran")))
+        .stdout(contains("[join] ran"))
+        .stderr(contains(
+            "Summary: 3 succeeded, 0 failed, 0 cancelled, 0 not started",
+        ));
+}
+
+#[test]
+fn jobs_fail_fast_cancels_running_tasks() {
+    let temp = tempdir().expect("create temp dir");
+    let script_path = temp.path().join("rhaskfile.rhai");
+    let mut file = fs::File::create(&script_path).expect("create script file");
+    writeln!(
+        file,
+        r#"
+            task("spin", || {{
+                actions(|| {{ loop {{ }} }});
+            }});
+            task("broken", || {{
+                actions(|| {{ throw "broken dependency"; }});
+            }});
+            task("release", || {{
+                deps(["spin", "broken"]);
+                actions(|| {{ print("[release] ran"); }});
+            }});
+        "#
+    )
+    .expect("write script");
+
+    rhask()
+        .args([
+            "--file",
+            script_path.to_str().expect("utf8 path"),
+            "-j",
+            "2",
+            "release",
+        ])
+        .timeout(std::time::Duration::from_secs(30))
+        .assert()
+        .failure()
+        .stdout(contains("[release] ran").not())
+        .stderr(contains("broken dependency"))
+        .stderr(contains("1 failed"))
+        .stderr(contains("not started"));
+}
+
+#[test]
+fn outputs_newer_than_sources_skip_actions_unless_forced() {
+    let temp = tempdir().expect("create temp dir");
+    let root = temp.path();
+    let script_path = root.join("rhaskfile.rhai");
+    let mut file = fs::File::create(&script_path).expect("create script file");
+    writeln!(
+        file,
+        r#"
+            task("codegen", || {{
+                sources(["proto/*.proto"]);
+                outputs(["gen/**"]);
+                actions(|| {{ print("[codegen] ran"); }});
+            }});
+        "#
+    )
+    .expect("write script");
+
+    let set_age = |path: &std::path::Path, secs: u64| {
+        fs::create_dir_all(path.parent().unwrap()).expect("create parent dir");
+        let file = fs::File::create(path).expect("create file");
+        file.set_modified(std::time::SystemTime::now() - std::time::Duration::from_secs(secs))
+            .expect("set modified time");
+    };
+    set_age(&root.join("proto/api.proto"), 60);
+    set_age(&root.join("gen/nested/api.rs"), 10);
+    let script = script_path.to_str().expect("utf8 path");
+
+    let output = rhask()
+        .args(["--file", script, "codegen"])
+        .output()
+        .expect("run rhask");
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("[codegen] ran"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("is up to date; skipping"));
+
+    rhask()
+        .args(["--file", script, "run", "--force", "codegen"])
+        .assert()
+        .success()
+        .stdout(contains("[codegen] ran"));
+
+    set_age(&root.join("proto/api.proto"), 0);
+    let output = rhask()
+        .args(["--file", script, "codegen"])
+        .output()
+        .expect("run rhask");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("[codegen] ran"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("is out of date"));
+}
+
+#[test]
+fn fingerprint_skips_unchanged_inputs_until_cache_clean() {
+    let temp = tempdir().expect("create temp dir");
+    let root = temp.path();
+    let script_path = root.join("rhaskfile.rhai");
+    let mut file = fs::File::create(&script_path).expect("create script file");
+    writeln!(
+        file,
+        r#"
+            task("gen", || {{
+                fingerprint();
+                sources(["inputs/*.txt"]);
+                actions(|| {{ print("[gen] ran"); }});
+            }});
+        "#
+    )
+    .expect("write script");
+    fs::create_dir_all(root.join("inputs")).expect("create inputs dir");
+    fs::write(root.join("inputs/a.txt"), "one").expect("write input");
+    let script = script_path.to_str().expect("utf8 path");
+    let run = || {
+        let output = rhask()
+            .args(["--file", script, "gen"])
+            .output()
+            .expect("run rhask");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).expect("utf8 stdout")
+    };
+
+    assert!(run().contains("[gen] ran"));
+    assert!(root.join(".rhask/cache/gen").is_file());
+    assert!(!run().contains("[gen] ran"));
+
+    fs::write(root.join("inputs/a.txt"), "one").expect("rewrite input");
+    assert!(!run().contains("[gen] ran"));
+
+    fs::write(root.join("inputs/a.txt"), "two").expect("modify input");
+    assert!(run().contains("[gen] ran"));
+    assert!(!run().contains("[gen] ran"));
+
+    let edited = fs::read_to_string(&script_path)
+        .expect("read script")
+        .replace("[gen] ran", "[gen] ran (edited)");
+    fs::write(&script_path, edited).expect("edit script");
+    assert!(run().contains("[gen] ran (edited)"));
+    assert!(!run().contains("[gen] ran"));
+
+    rhask()
+        .args(["--file", script, "cache", "clean"])
+        .assert()
+        .success()
+        .stdout(contains("Removed"));
+    assert!(!root.join(".rhask").join("cache").exists());
+    assert!(run().contains("[gen] ran"));
+}
+
+#[test]
+fn status_reports_up_to_date_stale_and_never_run_tasks() {
+    let temp = tempdir().expect("create temp dir");
+    let root = temp.path();
+    let script_path = root.join("rhaskfile.rhai");
+    let mut file = fs::File::create(&script_path).expect("create script file");
+    writeln!(
+        file,
+        r#"
+            group("gen", || {{
+                task("fresh", || {{
+                    sources(["fresh.in"]);
+                    outputs(["fresh.out"]);
+                    actions(|| {{}});
+                }});
+                task("stale", || {{
+                    sources(["stale.in"]);
+                    outputs(["stale.out"]);
+                    actions(|| {{}});
+                }});
+                task("hashed", || {{
+                    fingerprint();
+                    sources(["hashed.in"]);
+                    actions(|| {{}});
+                }});
+            }});
+            task("plain", || {{ actions(|| {{}}); }});
+        "#
+    )
+    .expect("write script");
+    let set_age = |name: &str, secs: u64| {
+        let file = fs::File::create(root.join(name)).expect("create file");
+        file.set_modified(std::time::SystemTime::now() - std::time::Duration::from_secs(secs))
+            .expect("set modified time");
+    };
+    set_age("fresh.in", 60);
+    set_age("fresh.out", 10);
+    set_age("stale.out", 60);
+    set_age("stale.in", 10);
+    set_age("hashed.in", 10);
+    let script = script_path.to_str().expect("utf8 path");
+
+    rhask()
+        .args(["--file", script, "status"])
+        .assert()
+        .success()
+        .stdout(
+            contains("  - fresh  : up to date")
+                .and(contains("- stale  : stale (changed: stale.in)"))
+                .and(contains("- hashed : never run"))
+                .and(contains("- plain : always runs")),
+        );
+
+    rhask()
+        .args(["--file", script, "gen.hashed"])
+        .assert()
+        .success();
+    fs::write(root.join("hashed.in"), "changed").expect("modify input");
+    rhask()
+        .args(["--file", script, "status", "gen"])
+        .assert()
+        .success()
+        .stdout(contains("- hashed : stale (changed: hashed.in)").and(contains("plain").not()));
+}